@@ -0,0 +1,465 @@
+use rand::seq::SliceRandom;
+use rand::{Rng, RngCore};
+use std::error::Error;
+use std::fmt;
+
+/// The longest code that can be played. Longer codes have too many
+/// possibilities (10^7 and up) for the solver to keep track of.
+pub const MAX_LENGTH: usize = 6;
+
+/// How many guesses a code game allows unless --attempts says
+/// otherwise, as in the classic Mastermind board.
+pub const DEFAULT_ATTEMPTS: usize = 10;
+
+/// What kind of code is being guessed: how many digits, and whether a
+/// digit may appear more than once.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CodeRules {
+    pub length: usize,
+    pub repeats: bool,
+}
+
+impl CodeRules {
+    pub fn new(length: usize, repeats: bool) -> Result<CodeRules, String> {
+        if length == 0 || length > MAX_LENGTH {
+            return Err(format!("codes are 1 to {MAX_LENGTH} digits long, not {length}"));
+        }
+        Ok(CodeRules { length, repeats })
+    }
+
+    /// Every code these rules allow, in increasing order.
+    pub fn all_codes(&self) -> Vec<Code> {
+        let count = 10u32.pow(self.length as u32);
+        (0..count)
+            .map(|n| {
+                let digits = format!("{n:0width$}", width = self.length);
+                Code(digits.bytes().map(|digit| digit - b'0').collect())
+            })
+            .filter(|code| self.repeats || !code.has_repeats())
+            .collect()
+    }
+
+    /// Draw a secret code.
+    pub fn random_code(&self, rng: &mut dyn RngCore) -> Code {
+        if self.repeats {
+            Code((0..self.length).map(|_| rng.gen_range(0..10)).collect())
+        } else {
+            let mut digits: Vec<u8> = (0..10).collect();
+            digits.shuffle(rng);
+            digits.truncate(self.length);
+            Code(digits)
+        }
+    }
+}
+
+/// A code: a fixed number of digits, 0 to 9 each.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Code(Vec<u8>);
+
+impl Code {
+    pub fn digits(&self) -> &[u8] {
+        &self.0
+    }
+
+    fn has_repeats(&self) -> bool {
+        let mut seen = [false; 10];
+        self.0.iter().any(|&digit| std::mem::replace(&mut seen[digit as usize], true))
+    }
+
+    /// Compare a guess with this code (the secret).
+    pub fn score(&self, guess: &Code) -> Score {
+        let mut bulls = 0;
+        let mut secret_counts = [0usize; 10];
+        let mut guess_counts = [0usize; 10];
+        for (&secret, &guessed) in self.0.iter().zip(&guess.0) {
+            if secret == guessed {
+                bulls += 1;
+            }
+            secret_counts[secret as usize] += 1;
+            guess_counts[guessed as usize] += 1;
+        }
+
+        // Every digit the two share counts once, in the right place or
+        // not; the ones in the right place are the bulls.
+        let shared: usize = (0..10).map(|digit| secret_counts[digit].min(guess_counts[digit])).sum();
+        Score {
+            bulls,
+            cows: shared - bulls,
+        }
+    }
+}
+
+impl fmt::Display for Code {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for digit in &self.0 {
+            write!(f, "{digit}")?;
+        }
+        Ok(())
+    }
+}
+
+/// The answer to a guess: bulls are right digits in the right place,
+/// cows are right digits in the wrong place.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Score {
+    pub bulls: usize,
+    pub cows: usize,
+}
+
+impl fmt::Display for Score {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let plural = |count: usize| if count == 1 { "" } else { "s" };
+        write!(
+            f,
+            "{} bull{}, {} cow{}",
+            self.bulls,
+            plural(self.bulls),
+            self.cows,
+            plural(self.cows)
+        )
+    }
+}
+
+/// Why a line typed at the prompt isn't a usable code. None of these
+/// use up an attempt.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CodeError {
+    /// Nothing (or only whitespace) was typed.
+    Empty,
+    /// The input contains something other than digits.
+    NotDigits(String),
+    /// The code has the wrong number of digits.
+    WrongLength { found: usize, expected: usize },
+    /// A digit appears twice, and the rules don't allow that.
+    Repeated(char),
+}
+
+impl fmt::Display for CodeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            CodeError::Empty => write!(f, "Please type a code before pressing enter."),
+            CodeError::NotDigits(input) => {
+                write!(f, "'{input}' is not a code. Please use only the digits 0-9.")
+            }
+            CodeError::WrongLength { found, expected } => {
+                write!(f, "That code has {found} digits. The secret code has {expected}.")
+            }
+            CodeError::Repeated(digit) => {
+                write!(f, "The digit {digit} appears more than once, and every digit of the secret code is different.")
+            }
+        }
+    }
+}
+
+impl Error for CodeError {}
+
+/// Turn a line typed at the prompt into a code that fits `rules`.
+/// Surrounding whitespace is ignored.
+pub fn parse_code(input: &str, rules: CodeRules) -> Result<Code, CodeError> {
+    let input = input.trim();
+    if input.is_empty() {
+        return Err(CodeError::Empty);
+    }
+    if !input.chars().all(|c| c.is_ascii_digit()) {
+        return Err(CodeError::NotDigits(input.to_string()));
+    }
+    if input.len() != rules.length {
+        return Err(CodeError::WrongLength {
+            found: input.len(),
+            expected: rules.length,
+        });
+    }
+
+    let code = Code(input.bytes().map(|digit| digit - b'0').collect());
+    if !rules.repeats {
+        for (index, c) in input.char_indices() {
+            if input[..index].contains(c) {
+                return Err(CodeError::Repeated(c));
+            }
+        }
+    }
+    Ok(code)
+}
+
+/// A round of bulls and cows: the same shape as Game, with a code for
+/// a secret and a Score instead of an Ordering for an answer.
+#[derive(Debug, Clone)]
+pub struct CodeGame {
+    rules: CodeRules,
+    secret: Code,
+    history: Vec<(Code, Score)>,
+    max_attempts: Option<usize>,
+}
+
+/// The result of submitting one guess.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CodeOutcome {
+    pub guess: Code,
+    pub score: Score,
+    pub attempts: usize,
+    pub remaining: Option<usize>,
+}
+
+impl CodeOutcome {
+    pub fn is_win(&self) -> bool {
+        self.score.bulls == self.guess.digits().len()
+    }
+
+    /// True if this guess was wrong and it was the last one allowed.
+    pub fn is_loss(&self) -> bool {
+        !self.is_win() && self.remaining == Some(0)
+    }
+}
+
+impl CodeGame {
+    pub fn new(rules: CodeRules, rng: &mut dyn RngCore) -> CodeGame {
+        CodeGame::with_secret(rules, rules.random_code(rng))
+    }
+
+    pub fn with_secret(rules: CodeRules, secret: Code) -> CodeGame {
+        CodeGame {
+            rules,
+            secret,
+            history: Vec::new(),
+            max_attempts: None,
+        }
+    }
+
+    /// Limit the number of guesses. None means the player can keep
+    /// guessing forever.
+    pub fn with_max_attempts(mut self, max_attempts: Option<usize>) -> CodeGame {
+        self.max_attempts = max_attempts;
+        self
+    }
+
+    pub fn submit_guess(&mut self, guess: Code) -> CodeOutcome {
+        let score = self.secret.score(&guess);
+        self.history.push((guess.clone(), score));
+        CodeOutcome {
+            guess,
+            score,
+            attempts: self.history.len(),
+            remaining: self.remaining(),
+        }
+    }
+
+    pub fn rules(&self) -> CodeRules {
+        self.rules
+    }
+
+    pub fn secret(&self) -> &Code {
+        &self.secret
+    }
+
+    pub fn history(&self) -> &[(Code, Score)] {
+        &self.history
+    }
+
+    pub fn attempts(&self) -> usize {
+        self.history.len()
+    }
+
+    pub fn remaining(&self) -> Option<usize> {
+        self.max_attempts
+            .map(|max| max.saturating_sub(self.attempts()))
+    }
+
+    pub fn is_won(&self) -> bool {
+        self.history.last().is_some_and(|(guess, _)| *guess == self.secret)
+    }
+
+    pub fn is_lost(&self) -> bool {
+        !self.is_won() && self.remaining() == Some(0)
+    }
+
+    pub fn is_over(&self) -> bool {
+        self.is_won() || self.is_lost()
+    }
+}
+
+/// Above this many codes still possible, comparing every guess against
+/// every one of them takes too long, and the solver simply plays the
+/// first code that is still possible.
+const MAX_COMPARED: usize = 2_000;
+
+/// Comparing every code the rules allow against the ones still possible
+/// is only worth it up to this many scores.
+const FULL_SEARCH_LIMIT: usize = 4_000_000;
+
+/// Knuth's strategy for Mastermind, applied to bulls and cows.
+///
+/// The solver keeps every code that is consistent with the answers so
+/// far. Each guess is the code whose worst answer leaves the fewest of
+/// them, preferring codes that could still be the secret when there is
+/// a tie (so the guess might win outright). With four digits and no
+/// repeats that never takes more than seven guesses.
+pub struct KnuthSolver {
+    rules: CodeRules,
+    all: Vec<Code>,
+    candidates: Vec<Code>,
+    guessed: bool,
+}
+
+impl KnuthSolver {
+    pub fn new(rules: CodeRules) -> KnuthSolver {
+        let all = rules.all_codes();
+        KnuthSolver {
+            rules,
+            candidates: all.clone(),
+            all,
+            guessed: false,
+        }
+    }
+
+    /// The codes that are still consistent with every answer.
+    pub fn candidates(&self) -> &[Code] {
+        &self.candidates
+    }
+
+    /// The first guess is always the same, so it is fixed rather than
+    /// searched for: 0123... without repeats, or Knuth's "1122" pattern
+    /// (half one digit, half another) with them.
+    fn opening(&self) -> Code {
+        let digits = (0..self.rules.length)
+            .map(|index| {
+                if self.rules.repeats {
+                    (index * 2 / self.rules.length) as u8
+                } else {
+                    index as u8
+                }
+            })
+            .collect();
+        Code(digits)
+    }
+
+    /// The size of the largest group of candidates that would all get
+    /// the same answer to `guess`.
+    fn worst_case(&self, guess: &Code) -> usize {
+        let width = self.rules.length + 1;
+        let mut groups = vec![0; width * width];
+        for candidate in &self.candidates {
+            let score = candidate.score(guess);
+            groups[score.bulls * width + score.cows] += 1;
+        }
+        groups.into_iter().max().unwrap_or(0)
+    }
+
+    pub fn next_guess(&mut self) -> Code {
+        if !self.guessed {
+            return self.opening();
+        }
+        if self.candidates.len() <= 2 || self.candidates.len() > MAX_COMPARED {
+            return self.candidates[0].clone();
+        }
+
+        let pool = if self.all.len() * self.candidates.len() <= FULL_SEARCH_LIMIT {
+            &self.all
+        } else {
+            &self.candidates
+        };
+
+        // min_by_key keeps the first of equal keys, and both lists are
+        // sorted, so ties go to the lowest code.
+        pool.iter()
+            .min_by_key(|&guess| {
+                let possible = self.candidates.binary_search(guess).is_ok();
+                (self.worst_case(guess), !possible)
+            })
+            .cloned()
+            .unwrap_or_else(|| self.candidates[0].clone())
+    }
+
+    /// Tell the solver the answer to its last guess.
+    pub fn feedback(&mut self, guess: &Code, score: Score) {
+        self.guessed = true;
+        self.candidates.retain(|candidate| candidate.score(guess) == score);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn code(digits: &str) -> Code {
+        Code(digits.bytes().map(|digit| digit - b'0').collect())
+    }
+
+    fn score(secret: &str, guess: &str) -> (usize, usize) {
+        let score = code(secret).score(&code(guess));
+        (score.bulls, score.cows)
+    }
+
+    #[test]
+    fn bulls_and_cows_are_counted() {
+        assert_eq!(score("1234", "1234"), (4, 0));
+        assert_eq!(score("1234", "4321"), (0, 4));
+        assert_eq!(score("1234", "1243"), (2, 2));
+        assert_eq!(score("1234", "5678"), (0, 0));
+        assert_eq!(score("1234", "5271"), (1, 1));
+    }
+
+    #[test]
+    fn a_repeated_digit_counts_only_as_often_as_the_secret_has_it() {
+        assert_eq!(score("1234", "1111"), (1, 0));
+        assert_eq!(score("1234", "2111"), (0, 2));
+        assert_eq!(score("1122", "1111"), (2, 0));
+        assert_eq!(score("1122", "2211"), (0, 4));
+        assert_eq!(score("1112", "2111"), (2, 2));
+        assert_eq!(score("1233", "3331"), (1, 2));
+        assert_eq!(score("0012", "1200"), (0, 4));
+    }
+
+    #[test]
+    fn scoring_is_symmetric() {
+        let rules = CodeRules::new(3, true).unwrap();
+        let codes = rules.all_codes();
+        for secret in codes.iter().step_by(37) {
+            for guess in codes.iter().step_by(11) {
+                assert_eq!(secret.score(guess), guess.score(secret), "{secret} {guess}");
+            }
+        }
+    }
+
+    #[test]
+    fn scores_are_written_out() {
+        assert_eq!(code("1243").score(&code("1234")).to_string(), "2 bulls, 2 cows");
+        assert_eq!(code("1234").score(&code("1567")).to_string(), "1 bull, 0 cows");
+        assert_eq!(code("0123").to_string(), "0123");
+    }
+
+    #[test]
+    fn all_codes_follow_the_rules() {
+        assert_eq!(CodeRules::new(4, false).unwrap().all_codes().len(), 5040);
+        assert_eq!(CodeRules::new(4, true).unwrap().all_codes().len(), 10_000);
+        assert!(CodeRules::new(0, false).is_err());
+        assert!(CodeRules::new(MAX_LENGTH + 1, true).is_err());
+    }
+
+    /// How many guesses the solver needs to find `secret`.
+    fn solve(rules: CodeRules, secret: &Code) -> usize {
+        let mut solver = KnuthSolver::new(rules);
+        for guesses in 1.. {
+            let guess = solver.next_guess();
+            let score = secret.score(&guess);
+            if score.bulls == rules.length {
+                return guesses;
+            }
+            solver.feedback(&guess, score);
+            assert!(solver.candidates().contains(secret), "{guess} ruled out {secret}");
+        }
+        unreachable!()
+    }
+
+    #[test]
+    fn knuth_solves_four_digits_within_seven_guesses() {
+        // Checking every one of the 5040 secrets takes minutes, so this
+        // plays a few of the 191 that need all seven guesses (1948,
+        // 5219 and 9871), and two that don't.
+        let rules = CodeRules::new(4, false).unwrap();
+        for secret in ["1948", "5219", "9871"] {
+            assert_eq!(solve(rules, &code(secret)), 7, "{secret}");
+        }
+        assert_eq!(solve(rules, &code("0123")), 1);
+        assert!(solve(rules, &code("3210")) <= 7);
+    }
+}
@@ -0,0 +1,751 @@
+use crate::code::{self, CodeRules};
+use crate::feedback::FeedbackMode;
+use crate::game::{optimal_attempts, range_size};
+use crate::hotseat;
+use crate::http;
+use crate::leaderboard;
+use crate::liar;
+use crate::save;
+use crate::number::{self, Number, NumberType, NumberVisitor};
+use crate::protocol::Protocol;
+use crate::secret::Distribution;
+use crate::server;
+use crate::tournament;
+use crate::word;
+use serde::{Deserialize, Serialize};
+use std::env;
+use std::fmt;
+use std::path::PathBuf;
+use std::time::Duration;
+
+/// Named presets for the range the secret number is drawn from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Difficulty {
+    Easy,
+    Normal,
+    Hard,
+    Insane,
+}
+
+impl Difficulty {
+    pub const ALL: [Difficulty; 4] = [
+        Difficulty::Easy,
+        Difficulty::Normal,
+        Difficulty::Hard,
+        Difficulty::Insane,
+    ];
+
+    /// The inclusive (low, high) bounds for this preset.
+    pub fn bounds(&self) -> (u32, u32) {
+        match self {
+            Difficulty::Easy => (1, 10),
+            Difficulty::Normal => (1, 100),
+            Difficulty::Hard => (1, 10_000),
+            Difficulty::Insane => (1, u32::MAX),
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Difficulty::Easy => "easy",
+            Difficulty::Normal => "normal",
+            Difficulty::Hard => "hard",
+            Difficulty::Insane => "insane",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Difficulty> {
+        Difficulty::ALL
+            .into_iter()
+            .find(|difficulty| difficulty.name().eq_ignore_ascii_case(name))
+    }
+}
+
+impl fmt::Display for Difficulty {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+/// Who plays the game.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Mode {
+    /// The program picks the secret and a human guesses it.
+    Human,
+    /// The program picks the secret and the built-in solver guesses it.
+    Auto,
+    /// The human thinks of the secret and the program guesses it.
+    Reverse,
+    /// Several humans take turns at the same terminal.
+    HotSeat,
+    /// Host multiplayer rooms over TCP (the `serve` subcommand).
+    Serve,
+    /// Play on a server with the line-based client (the `connect`
+    /// subcommand).
+    Connect,
+    /// Carry on with a saved game (the `load` subcommand).
+    Load,
+    /// Play back a transcript (the `replay` subcommand).
+    Replay,
+    /// Serve games as a JSON API over HTTP (the `http` subcommand).
+    Http,
+    /// Pit solver strategies against each other (the `tournament`
+    /// subcommand).
+    Tournament,
+}
+
+/// Everything the player chose on the command line.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Config {
+    pub low: u32,
+    pub high: u32,
+    /// The preset the bounds came from, or None if either bound was
+    /// overridden with --min/--max.
+    pub difficulty: Option<Difficulty>,
+    /// Seed for the random number generator, so a game can be replayed
+    /// exactly.
+    pub seed: Option<u64>,
+    /// How many guesses the player gets, or None for no limit.
+    pub max_attempts: Option<usize>,
+    /// The name wins are recorded under on the leaderboard.
+    pub name: String,
+    /// Where the leaderboard is stored (see leaderboard::default_path).
+    pub leaderboard_path: PathBuf,
+    /// Print the leaderboard instead of playing.
+    pub show_leaderboard: bool,
+    /// Where unfinished games are saved (see save::default_path).
+    pub save_path: PathBuf,
+    /// Write a transcript of the game here (see transcript::Transcript).
+    pub record_path: Option<PathBuf>,
+    /// The transcript to play back in replay mode.
+    pub replay_path: PathBuf,
+    /// How many times faster than the original a replay runs, or 0 to
+    /// show it all at once.
+    pub replay_speed: f64,
+    /// Who picks the secret and who guesses it.
+    pub mode: Mode,
+    /// The players' names in turn order, for hot-seat games.
+    pub players: Vec<String>,
+    /// In a hot-seat game, give every player their own secret instead
+    /// of racing for the same one.
+    pub separate_secrets: bool,
+    /// The address to serve on or connect to.
+    pub address: String,
+    /// How long the HTTP API keeps a game nobody asks about.
+    pub session_timeout: Duration,
+    /// How many games every strategy plays in a tournament.
+    pub games: usize,
+    /// The built-in strategies in a tournament (see
+    /// tournament::BUILTIN).
+    pub strategies: Vec<String>,
+    /// Programs that play in a tournament as strategies of their own,
+    /// over the JSON-lines protocol.
+    pub strategy_commands: Vec<String>,
+    /// Play in the full-screen terminal UI instead of line by line.
+    pub tui: bool,
+    /// How wrong guesses are answered.
+    pub feedback: FeedbackMode,
+    /// How many times the host may lie about "Too small!" and "Too
+    /// big!" (Ulam's game). 0 is the ordinary, honest game.
+    pub lies: usize,
+    /// Play against the evil host, which puts off choosing the secret
+    /// to force the worst case.
+    pub evil: bool,
+    /// How the secret is drawn from the range.
+    pub secret: Distribution,
+    /// Play bulls and cows with a code like these rules describe,
+    /// instead of guessing a number.
+    pub code: Option<CodeRules>,
+    /// Play the word game (like Wordle) instead of guessing a number.
+    pub word: bool,
+    /// The word list to play with, or None for the bundled English
+    /// one.
+    pub word_list: Option<PathBuf>,
+    /// Play over another Number type instead of u32 (--type). `low`
+    /// and `high` are only used for u32 games.
+    pub typed: Option<TypedRange>,
+    /// Let a program play through a machine-readable protocol on
+    /// standard input and output instead of a human.
+    pub protocol: Option<Protocol>,
+}
+
+/// The range of a game over a Number type picked at runtime. The bounds
+/// stay as typed until the type is known, since they may be negative,
+/// fractional or too big for a u32.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TypedRange {
+    pub kind: NumberType,
+    pub difficulty: Difficulty,
+    pub min: Option<String>,
+    pub max: Option<String>,
+    pub tolerance: Option<String>,
+}
+
+impl TypedRange {
+    /// The range and tolerance as T, which should be `kind`.
+    pub fn bounds<T: Number>(&self) -> Result<number::Bounds<T>, String> {
+        number::bounds(
+            self.difficulty.bounds(),
+            self.difficulty == Difficulty::Insane,
+            self.min.as_deref(),
+            self.max.as_deref(),
+            self.tolerance.as_deref(),
+        )
+    }
+}
+
+/// Checks the bounds of a typed game and works out how many guesses
+/// binary search needs over them.
+struct TypedAttempts<'a>(&'a TypedRange);
+
+impl NumberVisitor for TypedAttempts<'_> {
+    type Output = Result<usize, String>;
+
+    fn visit<T: Number>(self) -> Result<usize, String> {
+        let bounds = self.0.bounds::<T>()?;
+        Ok(T::attempts_needed(bounds.low, bounds.high, bounds.tolerance))
+    }
+}
+
+/// The replay speeds --speed takes, besides 0 for no pauses. Much
+/// slower than 0.01 and a pause could last for years.
+const REPLAY_SPEEDS: std::ops::RangeInclusive<f64> = 0.01..=1000.0;
+
+pub const USAGE: &str = "\
+Usage: guessing_game [OPTIONS]
+       guessing_game serve [OPTIONS]    host multiplayer rooms over TCP
+       guessing_game connect [OPTIONS]  join a server's rooms
+       guessing_game load [OPTIONS]     carry on with a saved game
+       guessing_game http [OPTIONS]     serve games as a JSON API over
+                                        HTTP on localhost
+       guessing_game tournament [OPTIONS]
+                                        pit solver strategies against
+                                        the same seeded secrets
+       guessing_game replay <PATH> [--speed <X>]
+                                        play back a transcript and check
+                                        it against its seed
+
+Options:
+  -d, --difficulty <NAME>  easy (1-10), normal (1-100), hard (1-10000)
+                           or insane (1-4294967295) [default: normal]
+      --min <N>            lowest possible secret number
+      --max <N>            highest possible secret number
+  -t, --type <TYPE>        the secret's type: i8, i16, i32, i64, i128,
+                           isize, u8, u16, u32, u64, u128, usize, f32 or
+                           f64 (negative --min works with signed types,
+                           insane is the type's full range) [default: u32]
+      --tolerance <X>      with f32 or f64, how close a guess must be to
+                           win [default: 0.01]
+      --seed <N>           seed the random number generator so the same
+                           seed always picks the same secret
+  -s, --secret <DIST>      how the secret is drawn: uniform, normal,
+                           triangular, primes, even, odd, or
+                           list:N,N,... [default: uniform]
+  -a, --attempts <N>       maximum number of guesses, or \"unlimited\"
+                           [default: enough for a binary search]
+  -n, --name <NAME>        name to record wins under on the leaderboard
+                           [default: $USER]
+  -f, --feedback <MODE>    \"ordering\" (too small/too big) or \"hotcold\"
+                           (freezing to burning) [default: ordering]
+      --tui                play in a full-screen terminal UI (falls back
+                           to line mode when input or output is piped)
+      --lies <K>           the host may lie about too small/too big up to
+                           K times [default: 0]
+  -c, --code <LENGTH>      guess a secret code of LENGTH digits (1-6)
+                           instead of a number, scored in bulls (right
+                           digit, right place) and cows (right digit,
+                           wrong place) [default attempts: 10]
+      --repeats            let a digit appear more than once in the code
+  -w, --word               guess a five-letter word instead of a number,
+                           with green, yellow and gray letters
+                           [default attempts: 6]
+      --words <PATH>       play the word game with the words in PATH, one
+                           per line, e.g. for another language
+      --evil               the host doesn't pick a secret up front and
+                           answers to keep as many numbers alive as it can
+      --auto               let the computer play using binary search
+      --reverse            you think of a number and the computer guesses
+  -p, --players <N|NAMES>  hot-seat game for 2-8 players, given as a count
+                           or a comma separated list of names
+      --separate-secrets   in a hot-seat game, every player gets their own
+                           secret and the fewest guesses wins
+      --address <ADDR>     address to serve on or connect to
+                           [default: 127.0.0.1:7878, or 127.0.0.1:8080
+                           for http]
+      --session-timeout <SECS>
+                           how long the HTTP API keeps a game after its
+                           last request [default: 600]
+      --leaderboard        show the high scores for each difficulty
+      --leaderboard-file <PATH>
+                           where to keep the leaderboard [default:
+                           <data dir>/guessing_game/leaderboard.json]
+      --save-file <PATH>   where unfinished games are saved when you type
+                           \"save\", and after every guess and on quit
+                           unless that would replace another game's save
+                           [default: <data dir>/guessing_game/save.json]
+      --record <PATH>      write a transcript of the game to PATH
+      --speed <X>          replay X times faster (0.01 to 1000), or 0 for
+                           no pauses [default: 1]
+      --protocol jsonl     for bots: read {\"guess\": N} lines from standard
+                           input and answer each with a JSON line
+      --games <N>          how many games each strategy plays in a
+                           tournament [default: 1000]
+      --strategies <LIST>  the built-in strategies in a tournament, comma
+                           separated: binary, random, linear, golden
+                           [default: all of them]
+      --strategy-command <CMD>
+                           add a program to the tournament that plays
+                           like a bot with --protocol jsonl (may be given
+                           more than once)
+  -h, --help               print this help";
+
+impl Config {
+    /// Build a Config from the command line arguments. The first
+    /// argument is the program name and is skipped.
+    pub fn build(mut args: impl Iterator<Item = String>) -> Result<Config, String> {
+        args.next();
+
+        let mut difficulty = Difficulty::Normal;
+        let mut min = None;
+        let mut max = None;
+        let mut seed = None;
+        let mut attempts = None;
+        let mut name = None;
+        let mut leaderboard_path = None;
+        let mut save_path = None;
+        let mut record_path = None;
+        let mut replay_path = None;
+        let mut replay_speed = None;
+        let mut show_leaderboard = false;
+        let mut mode = Mode::Human;
+        let mut players = Vec::new();
+        let mut separate_secrets = false;
+        let mut address = None;
+        let mut session_timeout = None;
+        let mut games = None;
+        let mut strategies = None;
+        let mut strategy_commands = Vec::new();
+        let mut tui = false;
+        let mut feedback = FeedbackMode::Ordering;
+        let mut lies = 0;
+        let mut evil = false;
+        let mut secret = Distribution::Uniform;
+        let mut code_length = None;
+        let mut repeats = false;
+        let mut word = false;
+        let mut word_list = None;
+        let mut number_type = None;
+        let mut tolerance = None;
+        let mut protocol = None;
+
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "-d" | "--difficulty" => {
+                    let preset = next_value(&mut args, &arg)?;
+                    difficulty = Difficulty::from_name(&preset)
+                        .ok_or_else(|| format!("unknown difficulty '{preset}'"))?;
+                }
+                "--min" => min = Some(next_value(&mut args, &arg)?),
+                "--max" => max = Some(next_value(&mut args, &arg)?),
+                "-t" | "--type" => {
+                    let value = next_value(&mut args, &arg)?;
+                    number_type = Some(
+                        NumberType::from_name(&value)
+                            .ok_or_else(|| format!("unknown number type '{value}'"))?,
+                    );
+                }
+                "--tolerance" => tolerance = Some(next_value(&mut args, &arg)?),
+                "--seed" => {
+                    let value = next_value(&mut args, &arg)?;
+                    seed = Some(value.parse().map_err(|_| {
+                        format!("'{value}' is not a valid seed (expected 0 to {})", u64::MAX)
+                    })?);
+                }
+                "-s" | "--secret" => secret = Distribution::from_name(&next_value(&mut args, &arg)?)?,
+                "-a" | "--attempts" => {
+                    let value = next_value(&mut args, &arg)?;
+                    attempts = Some(parse_attempts(&value)?);
+                }
+                "-n" | "--name" => name = Some(next_value(&mut args, &arg)?),
+                "--leaderboard" => show_leaderboard = true,
+                "--tui" => tui = true,
+                "-f" | "--feedback" => {
+                    let value = next_value(&mut args, &arg)?;
+                    feedback = FeedbackMode::from_name(&value)
+                        .ok_or_else(|| format!("unknown feedback mode '{value}'"))?;
+                }
+                "--lies" => {
+                    let value = next_value(&mut args, &arg)?;
+                    lies = value
+                        .parse()
+                        .map_err(|_| format!("'{value}' is not a valid number of lies"))?;
+                }
+                "--evil" => evil = true,
+                "-c" | "--code" => {
+                    let value = next_value(&mut args, &arg)?;
+                    code_length = Some(value.parse().map_err(|_| format!("'{value}' is not a valid code length"))?);
+                }
+                "--repeats" => repeats = true,
+                "-w" | "--word" => word = true,
+                "--words" => {
+                    word = true;
+                    word_list = Some(PathBuf::from(next_value(&mut args, &arg)?));
+                }
+                "--auto" => set_mode(&mut mode, Mode::Auto, &arg)?,
+                "--reverse" => set_mode(&mut mode, Mode::Reverse, &arg)?,
+                "-p" | "--players" => {
+                    set_mode(&mut mode, Mode::HotSeat, &arg)?;
+                    players = hotseat::parse_players(&next_value(&mut args, &arg)?)?;
+                }
+                "--separate-secrets" => separate_secrets = true,
+                "serve" => set_mode(&mut mode, Mode::Serve, &arg)?,
+                "connect" => set_mode(&mut mode, Mode::Connect, &arg)?,
+                "load" => set_mode(&mut mode, Mode::Load, &arg)?,
+                "http" => set_mode(&mut mode, Mode::Http, &arg)?,
+                "tournament" => set_mode(&mut mode, Mode::Tournament, &arg)?,
+                "--games" => {
+                    let value = next_value(&mut args, &arg)?;
+                    games = Some(
+                        value
+                            .parse()
+                            .ok()
+                            .filter(|&games| games > 0)
+                            .ok_or_else(|| format!("'{value}' is not a valid number of games"))?,
+                    );
+                }
+                "--strategies" => strategies = Some(parse_strategies(&next_value(&mut args, &arg)?)?),
+                "--strategy-command" => strategy_commands.push(next_value(&mut args, &arg)?),
+                "replay" => {
+                    set_mode(&mut mode, Mode::Replay, &arg)?;
+                    replay_path = Some(PathBuf::from(next_value(&mut args, &arg)?));
+                }
+                "--speed" => {
+                    let value = next_value(&mut args, &arg)?;
+                    replay_speed = Some(
+                        value
+                            .parse::<f64>()
+                            .ok()
+                            .filter(|speed| *speed == 0.0 || REPLAY_SPEEDS.contains(speed))
+                            .ok_or_else(|| {
+                                format!("'{value}' is not a valid replay speed (expected 0, or 0.01 to 1000)")
+                            })?,
+                    );
+                }
+                "--protocol" => {
+                    let value = next_value(&mut args, &arg)?;
+                    protocol = Some(
+                        Protocol::from_name(&value)
+                            .ok_or_else(|| format!("unknown protocol '{value}'"))?,
+                    );
+                }
+                "--record" => record_path = Some(PathBuf::from(next_value(&mut args, &arg)?)),
+                "--save-file" => save_path = Some(PathBuf::from(next_value(&mut args, &arg)?)),
+                "--address" => address = Some(next_value(&mut args, &arg)?),
+                "--session-timeout" => {
+                    let value = next_value(&mut args, &arg)?;
+                    session_timeout = Some(Duration::from_secs(
+                        value
+                            .parse()
+                            .ok()
+                            .filter(|&seconds| seconds > 0)
+                            .ok_or_else(|| format!("'{value}' is not a valid session timeout (expected seconds)"))?,
+                    ));
+                }
+                "--leaderboard-file" => {
+                    leaderboard_path = Some(PathBuf::from(next_value(&mut args, &arg)?));
+                }
+                "-h" | "--help" => return Err(USAGE.to_string()),
+                _ => return Err(format!("unexpected argument '{arg}'\n\n{USAGE}")),
+            }
+        }
+
+        if tui && mode != Mode::Human {
+            return Err(String::from("--tui only works for a single human player"));
+        }
+        if feedback == FeedbackMode::HotCold && (tui || !matches!(mode, Mode::Human | Mode::HotSeat)) {
+            return Err(String::from(
+                "hot/cold feedback is only for human players in line mode",
+            ));
+        }
+        if lies > 0 && (tui || feedback != FeedbackMode::Ordering || !matches!(mode, Mode::Human | Mode::Auto)) {
+            return Err(String::from(
+                "--lies only works for a single player (or --auto) with too small/too big feedback in line mode",
+            ));
+        }
+        if evil && (lies > 0 || tui || feedback != FeedbackMode::Ordering || !matches!(mode, Mode::Human | Mode::Auto)) {
+            return Err(String::from(
+                "--evil only works for a single player (or --auto) with too small/too big feedback in line mode, without --lies",
+            ));
+        }
+        if secret != Distribution::Uniform && (evil || matches!(mode, Mode::Reverse | Mode::Connect | Mode::Load)) {
+            return Err(String::from(
+                "--secret only works when the program draws the secret (not with --evil, --reverse, connect or load)",
+            ));
+        }
+        let code = code_length
+            .map(|length| CodeRules::new(length, repeats))
+            .transpose()?;
+        if repeats && code.is_none() {
+            return Err(String::from("--repeats only makes sense with --code"));
+        }
+        if code.is_some()
+            && (tui
+                || evil
+                || lies > 0
+                || feedback != FeedbackMode::Ordering
+                || secret != Distribution::Uniform
+                || !matches!(mode, Mode::Human | Mode::Auto))
+        {
+            return Err(String::from(
+                "--code is for a single player (or --auto) in line mode, without the number game's options",
+            ));
+        }
+        if word
+            && (tui
+                || evil
+                || lies > 0
+                || code.is_some()
+                || feedback != FeedbackMode::Ordering
+                || secret != Distribution::Uniform
+                || mode != Mode::Human)
+        {
+            return Err(String::from(
+                "--word is for a single player in line mode, without the number game's options",
+            ));
+        }
+        if replay_speed.is_some() && mode != Mode::Replay {
+            return Err(String::from("--speed only makes sense with replay"));
+        }
+        if record_path.is_some()
+            && (evil
+                || lies > 0
+                || code.is_some()
+                || word
+                || number_type.is_some()
+                || !matches!(mode, Mode::Human | Mode::Auto | Mode::Load))
+        {
+            return Err(String::from(
+                "--record works for the number game played by one player, the computer, or a loaded game",
+            ));
+        }
+        if protocol.is_some()
+            && (tui
+                || evil
+                || lies > 0
+                || code.is_some()
+                || word
+                || number_type.is_some()
+                || record_path.is_some()
+                || feedback != FeedbackMode::Ordering
+                || mode != Mode::Human)
+        {
+            return Err(String::from(
+                "--protocol is for a program playing the number game on its own, without the other options",
+            ));
+        }
+        if session_timeout.is_some() && mode != Mode::Http {
+            return Err(String::from("--session-timeout only makes sense with http"));
+        }
+        if (games.is_some() || strategies.is_some() || !strategy_commands.is_empty()) && mode != Mode::Tournament {
+            return Err(String::from(
+                "--games, --strategies and --strategy-command only make sense with tournament",
+            ));
+        }
+        if mode == Mode::Tournament && attempts == Some(None) {
+            return Err(String::from(
+                "tournament games need an attempt limit: linear search over a big range could run for billions of guesses",
+            ));
+        }
+        if separate_secrets && mode != Mode::HotSeat {
+            return Err(String::from("--separate-secrets only makes sense with --players"));
+        }
+        if tolerance.is_some() && !number_type.is_some_and(|kind| kind.is_float()) {
+            return Err(String::from("--tolerance only makes sense with --type f32 or f64"));
+        }
+        if number_type.is_some()
+            && (tui
+                || evil
+                || lies > 0
+                || code.is_some()
+                || word
+                || feedback != FeedbackMode::Ordering
+                || secret != Distribution::Uniform
+                || !matches!(mode, Mode::Human | Mode::Auto))
+        {
+            return Err(String::from(
+                "--type is for a single player (or --auto) in line mode, without the u32 game's options",
+            ));
+        }
+
+        let overridden = min.is_some() || max.is_some();
+        let (preset_low, preset_high) = difficulty.bounds();
+        let (low, high) = match number_type {
+            Some(_) => (preset_low, preset_high),
+            None => {
+                let low = min.as_deref().map(parse_bound).transpose()?.unwrap_or(preset_low);
+                let high = max.as_deref().map(parse_bound).transpose()?.unwrap_or(preset_high);
+                (low, high)
+            }
+        };
+        validate_range(low, high)?;
+        // Catch distributions with nothing to draw, such as primes
+        // between 24 and 28, before the game starts.
+        secret.source(low, high)?;
+        let typed = number_type.map(|kind| TypedRange {
+            kind,
+            difficulty,
+            min,
+            max,
+            tolerance,
+        });
+        // A typed game checks its own range, and needs as many guesses
+        // as binary search over it takes.
+        let typed_attempts = typed
+            .as_ref()
+            .map(|typed| typed.kind.visit(TypedAttempts(typed)))
+            .transpose()?;
+
+        Ok(Config {
+            low,
+            high,
+            difficulty: if !overridden {
+                Some(difficulty)
+            } else {
+                None
+            },
+            seed,
+            // By default the player gets exactly as many guesses as a
+            // binary search needs, floor(log2(range size)) + 1, or with
+            // lies, as many as the lie-tolerant solver needs. Tournament
+            // games allow enough guesses for every strategy to finish,
+            // up to a ceiling that keeps big ranges from running forever.
+            max_attempts: attempts.unwrap_or(if mode == Mode::Tournament {
+                Some(tournament::default_max_attempts(low, high))
+            } else if let Some(typed_attempts) = typed_attempts {
+                Some(typed_attempts)
+            } else if word {
+                Some(word::DEFAULT_ATTEMPTS)
+            } else if code.is_some() {
+                Some(code::DEFAULT_ATTEMPTS)
+            } else if lies > 0 {
+                Some(liar::default_max_attempts(low, high, lies))
+            } else {
+                Some(optimal_attempts(range_size(low, high)))
+            }),
+            name: name
+                .or_else(|| env::var("USER").ok())
+                .or_else(|| env::var("USERNAME").ok())
+                .unwrap_or_else(|| String::from("player")),
+            leaderboard_path: leaderboard_path.unwrap_or_else(leaderboard::default_path),
+            show_leaderboard,
+            save_path: save_path.unwrap_or_else(save::default_path),
+            record_path,
+            replay_path: replay_path.unwrap_or_default(),
+            replay_speed: replay_speed.unwrap_or(1.0),
+            mode,
+            players,
+            separate_secrets,
+            address: address.unwrap_or_else(|| match mode {
+                Mode::Http => http::DEFAULT_ADDRESS.to_string(),
+                _ => server::DEFAULT_ADDRESS.to_string(),
+            }),
+            session_timeout: session_timeout.unwrap_or(http::DEFAULT_SESSION_TIMEOUT),
+            games: games.unwrap_or(1000),
+            strategies: strategies.unwrap_or_else(|| {
+                // Programs given with --strategy-command play on their
+                // own unless --strategies asks for company.
+                if strategy_commands.is_empty() {
+                    tournament::BUILTIN.iter().map(|name| name.to_string()).collect()
+                } else {
+                    Vec::new()
+                }
+            }),
+            strategy_commands,
+            tui,
+            feedback,
+            lies,
+            evil,
+            secret,
+            code,
+            word,
+            word_list,
+            typed,
+            protocol,
+        })
+    }
+
+    /// A short description of the active range for the banner, such
+    /// as "between 1 and 100 (normal)".
+    pub fn describe_range(&self) -> String {
+        match self.difficulty {
+            Some(difficulty) => format!("between {} and {} ({difficulty})", self.low, self.high),
+            None => format!("between {} and {}", self.low, self.high),
+        }
+    }
+}
+
+/// Reject ranges that leave nothing to guess (low == high) or that are
+/// the wrong way round (low > high).
+pub fn validate_range(low: u32, high: u32) -> Result<(), String> {
+    match low.cmp(&high) {
+        std::cmp::Ordering::Less => Ok(()),
+        std::cmp::Ordering::Equal => Err(format!(
+            "the range {low}..={high} has only one number; --max must be greater than --min"
+        )),
+        std::cmp::Ordering::Greater => Err(format!(
+            "the range is inverted: --min ({low}) is greater than --max ({high})"
+        )),
+    }
+}
+
+/// Switch from the default human mode, refusing to combine two modes
+/// such as --auto and --reverse.
+fn set_mode(mode: &mut Mode, new_mode: Mode, flag: &str) -> Result<(), String> {
+    if *mode != Mode::Human && *mode != new_mode {
+        return Err(format!("{flag} can't be combined with another game mode"));
+    }
+    *mode = new_mode;
+    Ok(())
+}
+
+fn next_value(args: &mut impl Iterator<Item = String>, flag: &str) -> Result<String, String> {
+    args.next()
+        .ok_or_else(|| format!("{flag} needs a value"))
+}
+
+fn parse_attempts(value: &str) -> Result<Option<usize>, String> {
+    if value.eq_ignore_ascii_case("unlimited") {
+        return Ok(None);
+    }
+    match value.parse() {
+        Ok(0) | Err(_) => Err(format!(
+            "'{value}' is not a valid number of attempts (expected a positive number or \"unlimited\")"
+        )),
+        Ok(attempts) => Ok(Some(attempts)),
+    }
+}
+
+/// Read a comma separated list of built-in tournament strategies.
+fn parse_strategies(value: &str) -> Result<Vec<String>, String> {
+    value
+        .split(',')
+        .map(|name| {
+            let name = name.trim().to_ascii_lowercase();
+            if tournament::BUILTIN.contains(&name.as_str()) {
+                Ok(name)
+            } else {
+                Err(format!(
+                    "unknown strategy '{name}' (expected {})",
+                    tournament::BUILTIN.join(", ")
+                ))
+            }
+        })
+        .collect()
+}
+
+fn parse_bound(value: &str) -> Result<u32, String> {
+    value
+        .parse()
+        .map_err(|_| format!("'{value}' is not a number between 0 and {}", u32::MAX))
+}
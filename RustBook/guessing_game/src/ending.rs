@@ -0,0 +1,45 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// Exit code for bad command line arguments.
+pub const USAGE_EXIT_CODE: i32 = 2;
+
+/// How a game ended. Each ending has its own process exit code, so
+/// scripts can tell a win from a loss from the player giving up.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Ending {
+    /// The player found the secret number (exit code 0).
+    Won,
+    /// The player ran out of attempts (exit code 1).
+    Lost,
+    /// The player typed quit/exit, pressed Ctrl-C, or closed standard
+    /// input (exit code 3).
+    Quit,
+    /// Something went wrong, such as a failure reading standard input
+    /// (exit code 4).
+    Error,
+}
+
+impl Ending {
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            Ending::Won => 0,
+            Ending::Lost => 1,
+            Ending::Quit => 3,
+            Ending::Error => 4,
+        }
+    }
+}
+
+impl fmt::Display for Ending {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let text = match self {
+            Ending::Won => "won",
+            Ending::Lost => "lost",
+            Ending::Quit => "quit",
+            Ending::Error => "stopped by an error",
+        };
+        write!(f, "{text}")
+    }
+}
@@ -0,0 +1,168 @@
+use crate::game::{Game, Host, Outcome};
+
+/// An adversarial host that never really picks a secret.
+///
+/// Instead of comparing each guess with a number chosen up front, the
+/// evil host answers "Too small!" or "Too big!" so that the larger part
+/// of the numbers still possible stays alive, and only says "You win!"
+/// when a single number is left and it gets guessed. Every player, even
+/// a perfect one, is pushed into the worst case: binary search needs all
+/// of its floor(log2(n)) + 1 guesses, and any other strategy needs at
+/// least as many.
+///
+/// The answers always stay consistent. The hosted Game holds a secret
+/// that fits every answer so far, and it is moved (within the numbers
+/// still possible) before each guess is answered, so the secret the
+/// game ends with is one that every answer was true for.
+pub struct EvilHost<'a> {
+    game: &'a mut Game,
+}
+
+impl<'a> EvilHost<'a> {
+    pub fn new(game: &'a mut Game) -> EvilHost<'a> {
+        let (low, high) = game.possible_range();
+        game.move_secret(low + (high - low) / 2);
+        EvilHost { game }
+    }
+
+    pub fn game(&self) -> &Game {
+        self.game
+    }
+
+    fn answer(&mut self, guess: u32) -> Outcome {
+        if !self.game.is_over() {
+            let (low, high) = self.game.possible_range();
+
+            // How many of the numbers still possible each answer would
+            // keep alive. A guess outside low..=high leaves one side
+            // empty, so it gets the same (true) answer as always.
+            let below = if guess > low {
+                u64::from(guess.min(high)) - u64::from(low) + u64::from(guess > high)
+            } else {
+                0
+            };
+            let above = if guess < high {
+                u64::from(high) - u64::from(guess.max(low)) + u64::from(guess < low)
+            } else {
+                0
+            };
+
+            // Keep the larger side (the upper one on a tie) by moving
+            // the secret into the middle of it. Only when the guess is
+            // the last number left does it become the secret.
+            let secret = if above == 0 && below == 0 {
+                guess
+            } else if above >= below {
+                let low = low.max(guess + 1);
+                low + (high - low) / 2
+            } else {
+                let high = high.min(guess - 1);
+                low + (high - low) / 2
+            };
+            self.game.move_secret(secret);
+        }
+
+        self.game.submit_guess(guess)
+    }
+}
+
+impl Host for EvilHost<'_> {
+    fn low(&self) -> u32 {
+        self.game.low()
+    }
+
+    fn high(&self) -> u32 {
+        self.game.high()
+    }
+
+    fn remaining(&self) -> Option<usize> {
+        self.game.remaining()
+    }
+
+    fn is_over(&self) -> bool {
+        self.game.is_over()
+    }
+
+    fn submit_guess(&mut self, guess: u32) -> Outcome {
+        self.answer(guess)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::seeded_rng;
+    use crate::solver::{BinarySearch, LinearSearch, Solver};
+    use rand::Rng;
+    use std::cmp::Ordering;
+
+    /// Play `solver` against an evil host over `low..=high` until it
+    /// wins, returning every guess with the answer it got and the
+    /// secret the game ended with.
+    fn play(low: u32, high: u32, solver: &mut dyn Solver) -> (Vec<(u32, Ordering)>, u32) {
+        let mut game = Game::with_secret(low, high, low);
+        let mut host = EvilHost::new(&mut game);
+        let mut answers = Vec::new();
+        while !host.is_over() {
+            let guess = solver.next_guess();
+            let outcome = host.submit_guess(guess);
+            answers.push((guess, outcome.ordering));
+            solver.feedback(guess, outcome.ordering);
+        }
+        (answers, host.game().secret())
+    }
+
+    /// True if every answer was true for `secret`.
+    fn consistent(answers: &[(u32, Ordering)], secret: u32) -> bool {
+        answers.iter().all(|&(guess, ordering)| guess.cmp(&secret) == ordering)
+    }
+
+    #[test]
+    fn binary_search_is_pushed_into_its_worst_case() {
+        for (high, worst) in [(2, 2), (3, 2), (100, 7), (127, 7), (128, 8), (1000, 10)] {
+            let (answers, secret) = play(1, high, &mut BinarySearch::new(1, high));
+            assert_eq!(answers.len(), worst, "1..={high}");
+            assert!(consistent(&answers, secret));
+        }
+    }
+
+    #[test]
+    fn linear_search_has_to_try_every_number() {
+        let (answers, secret) = play(1, 50, &mut LinearSearch::new(1));
+        assert_eq!((answers.len(), secret), (50, 50));
+        assert!(consistent(&answers, secret));
+    }
+
+    #[test]
+    fn every_answer_fits_the_final_secret() {
+        let mut rng = seeded_rng(Some(3));
+        for _ in 0..200 {
+            let mut game = Game::with_secret(10, 60, 10);
+            let mut host = EvilHost::new(&mut game);
+            let mut answers = Vec::new();
+            for _ in 0..500 {
+                // Some guesses fall outside the range, and some repeat.
+                let guess = rng.gen_range(0..=70);
+                let outcome = host.submit_guess(guess);
+                answers.push((guess, outcome.ordering));
+                if outcome.is_win() {
+                    break;
+                }
+                // The secret is always one of the numbers still possible.
+                let (low, high) = host.game().possible_range();
+                assert!((low..=high).contains(&host.game().secret()));
+            }
+            assert!(host.is_over());
+            assert!(consistent(&answers, host.game().secret()));
+        }
+    }
+
+    #[test]
+    fn guesses_outside_the_range_get_the_true_answer() {
+        let mut game = Game::with_secret(10, 20, 10);
+        let mut host = EvilHost::new(&mut game);
+        assert_eq!(host.submit_guess(5).ordering, Ordering::Less);
+        assert_eq!(host.submit_guess(25).ordering, Ordering::Greater);
+        assert_eq!(host.game().possible_range(), (10, 20));
+    }
+}
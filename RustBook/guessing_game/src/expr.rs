@@ -0,0 +1,596 @@
+use crate::number::Number;
+use std::error::Error;
+use std::fmt;
+
+/// The checked arithmetic the expression evaluator needs from a Number
+/// type. Every operation is done in the secret's own type, so
+/// `200 + 100` overflows a u8 just as it would in Rust.
+pub trait Arithmetic: Sized {
+    /// Read a literal in the given radix, with any underscores already
+    /// taken out.
+    fn from_literal(text: &str, radix: u32) -> Result<Self, LiteralError>;
+
+    fn checked_add(self, rhs: Self) -> Option<Self>;
+    fn checked_sub(self, rhs: Self) -> Option<Self>;
+    fn checked_mul(self, rhs: Self) -> Option<Self>;
+
+    /// The caller checks for a zero divisor first, so None means the
+    /// result overflowed (i32::MIN / -1 does).
+    fn checked_div(self, rhs: Self) -> Option<Self>;
+    fn checked_rem(self, rhs: Self) -> Option<Self>;
+
+    fn checked_neg(self) -> Option<Self>;
+    fn is_zero(&self) -> bool;
+}
+
+/// Why a literal couldn't be read.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LiteralError {
+    /// It isn't a number in its radix, like "12ab" or "0xg".
+    Invalid,
+    /// It has a fraction, and the secret is an integer.
+    NotWhole,
+    /// It is a number, but too big for the secret's type.
+    TooLarge,
+}
+
+macro_rules! integer {
+    ($($t:ident),*) => {$(
+        impl Arithmetic for $t {
+            fn from_literal(text: &str, radix: u32) -> Result<Self, LiteralError> {
+                if radix == 10 && text.contains('.') {
+                    return Err(LiteralError::NotWhole);
+                }
+                $t::from_str_radix(text, radix).map_err(|err| match err.kind() {
+                    std::num::IntErrorKind::PosOverflow => LiteralError::TooLarge,
+                    _ => LiteralError::Invalid,
+                })
+            }
+
+            fn checked_add(self, rhs: Self) -> Option<Self> {
+                $t::checked_add(self, rhs)
+            }
+
+            fn checked_sub(self, rhs: Self) -> Option<Self> {
+                $t::checked_sub(self, rhs)
+            }
+
+            fn checked_mul(self, rhs: Self) -> Option<Self> {
+                $t::checked_mul(self, rhs)
+            }
+
+            fn checked_div(self, rhs: Self) -> Option<Self> {
+                $t::checked_div(self, rhs)
+            }
+
+            fn checked_rem(self, rhs: Self) -> Option<Self> {
+                $t::checked_rem(self, rhs)
+            }
+
+            fn checked_neg(self) -> Option<Self> {
+                $t::checked_neg(self)
+            }
+
+            fn is_zero(&self) -> bool {
+                *self == 0
+            }
+        }
+    )*};
+}
+
+integer!(i8, i16, i32, i64, i128, isize, u8, u16, u32, u64, u128, usize);
+
+macro_rules! float {
+    ($($t:ident),*) => {$(
+        impl Arithmetic for $t {
+            fn from_literal(text: &str, radix: u32) -> Result<Self, LiteralError> {
+                let value = if radix == 10 {
+                    text.parse::<$t>().map_err(|_| LiteralError::Invalid)?
+                } else {
+                    // Hex, octal and binary literals are whole numbers.
+                    u128::from_str_radix(text, radix).map_err(|_| LiteralError::Invalid)? as $t
+                };
+                // "inf" and "nan" aren't lexed as literals, so only a
+                // literal too big for the type is infinite.
+                if value.is_finite() {
+                    Ok(value)
+                } else {
+                    Err(LiteralError::TooLarge)
+                }
+            }
+
+            fn checked_add(self, rhs: Self) -> Option<Self> {
+                Some(self + rhs).filter(|value| value.is_finite())
+            }
+
+            fn checked_sub(self, rhs: Self) -> Option<Self> {
+                Some(self - rhs).filter(|value| value.is_finite())
+            }
+
+            fn checked_mul(self, rhs: Self) -> Option<Self> {
+                Some(self * rhs).filter(|value| value.is_finite())
+            }
+
+            fn checked_div(self, rhs: Self) -> Option<Self> {
+                Some(self / rhs).filter(|value| value.is_finite())
+            }
+
+            fn checked_rem(self, rhs: Self) -> Option<Self> {
+                Some(self % rhs).filter(|value| value.is_finite())
+            }
+
+            fn checked_neg(self) -> Option<Self> {
+                Some(-self)
+            }
+
+            fn is_zero(&self) -> bool {
+                *self == 0.0
+            }
+        }
+    )*};
+}
+
+float!(f32, f64);
+
+/// An expression that couldn't be worked out, and the column (counting
+/// characters from 1) where the problem is.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExprError {
+    pub column: usize,
+    pub kind: ExprErrorKind,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ExprErrorKind {
+    /// A character that can't appear in an expression.
+    UnexpectedChar(char),
+    /// An operator or ')' where a number should be.
+    ExpectedNumber(String),
+    /// A number or '(' where an operator should be.
+    ExpectedOperator(String),
+    /// The input stops where a number should be.
+    UnexpectedEnd,
+    /// A '(' that is never closed.
+    UnclosedParen,
+    /// A ')' without a '(' before it.
+    UnmatchedParen,
+    InvalidLiteral(String),
+    /// A literal with a fraction in an integer game.
+    NotWhole { literal: String, type_name: &'static str },
+    /// A literal too big for the secret's type.
+    LiteralTooLarge { literal: String, type_name: &'static str },
+    /// An operation whose result doesn't fit in the secret's type.
+    Overflow { operator: char, type_name: &'static str },
+    DivisionByZero,
+    /// More than MAX_DEPTH parentheses or signs nested inside each
+    /// other.
+    TooDeep,
+}
+
+impl fmt::Display for ExprError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "column {}: ", self.column)?;
+        match &self.kind {
+            ExprErrorKind::UnexpectedChar(c) => write!(f, "unexpected '{c}'"),
+            ExprErrorKind::ExpectedNumber(found) => write!(f, "expected a number, found '{found}'"),
+            ExprErrorKind::ExpectedOperator(found) => {
+                write!(f, "expected an operator, found '{found}'")
+            }
+            ExprErrorKind::UnexpectedEnd => write!(f, "the expression ends where a number should be"),
+            ExprErrorKind::UnclosedParen => write!(f, "this '(' is never closed"),
+            ExprErrorKind::UnmatchedParen => write!(f, "this ')' has no '(' to close"),
+            ExprErrorKind::InvalidLiteral(literal) => write!(f, "'{literal}' is not a valid number"),
+            ExprErrorKind::NotWhole { literal, type_name } => {
+                write!(f, "'{literal}' has a fraction, which {type_name} can't hold")
+            }
+            ExprErrorKind::LiteralTooLarge { literal, type_name } => {
+                write!(f, "'{literal}' doesn't fit in {type_name}")
+            }
+            ExprErrorKind::Overflow { operator, type_name } => {
+                write!(f, "the result of '{operator}' doesn't fit in {type_name}")
+            }
+            ExprErrorKind::DivisionByZero => write!(f, "division by zero"),
+            ExprErrorKind::TooDeep => {
+                write!(f, "more than {MAX_DEPTH} parentheses or signs inside each other")
+            }
+        }
+    }
+}
+
+impl Error for ExprError {}
+
+impl ExprError {
+    /// The expression with a caret under the offending column, as two
+    /// lines.
+    pub fn pointer(&self, input: &str) -> String {
+        format!("{input}\n{:>width$}", "^", width = self.column)
+    }
+}
+
+/// Whether a guess that isn't a plain number is worth evaluating: it
+/// has an operator, a parenthesis or an underscore, or a 0x, 0o or 0b
+/// prefix. Anything else (like "abc") is simply not a number.
+pub fn looks_like_expression(input: &str) -> bool {
+    let input = input.trim().to_ascii_lowercase();
+    input.contains(['+', '-', '*', '/', '%', '(', ')', '_'])
+        || ["0x", "0o", "0b"].iter().any(|prefix| input.starts_with(prefix))
+}
+
+/// Work out an arithmetic expression typed as a guess, such as
+/// `50+25`, `(1000/3)`, `0x40` or `1_000`.
+///
+/// `+`, `-`, `*`, `/` and `%` work as in Rust: `*`, `/` and `%` bind
+/// tighter than `+` and `-`, operators of the same precedence go left
+/// to right, integer division rounds towards zero, and every step is
+/// checked for overflow in T. Literals can be decimal, hex (`0x`),
+/// octal (`0o`) or binary (`0b`), with underscores between digits, and
+/// in float games decimal ones can have a fraction and an exponent.
+pub fn evaluate<T: Number>(input: &str) -> Result<T, ExprError> {
+    let tokens = tokenize(input)?;
+    let mut parser = Parser {
+        tokens: &tokens,
+        position: 0,
+        depth: 0,
+    };
+    let value = parser.expression::<T>()?;
+
+    // Everything should have been used up; a ')' or another number
+    // left over is a mistake.
+    match parser.next() {
+        Token { kind: TokenKind::End, .. } => Ok(value),
+        Token { kind: TokenKind::Close, column } => Err(ExprError {
+            column,
+            kind: ExprErrorKind::UnmatchedParen,
+        }),
+        token => Err(ExprError {
+            column: token.column,
+            kind: ExprErrorKind::ExpectedOperator(token.text()),
+        }),
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum TokenKind {
+    /// A literal, as typed.
+    Literal(String),
+    Operator(char),
+    Open,
+    Close,
+    End,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Token {
+    kind: TokenKind,
+    column: usize,
+}
+
+impl Token {
+    /// The token as typed, for error messages.
+    fn text(&self) -> String {
+        match &self.kind {
+            TokenKind::Literal(literal) => literal.clone(),
+            TokenKind::Operator(operator) => operator.to_string(),
+            TokenKind::Open => String::from("("),
+            TokenKind::Close => String::from(")"),
+            TokenKind::End => String::new(),
+        }
+    }
+}
+
+fn tokenize(input: &str) -> Result<Vec<Token>, ExprError> {
+    let chars: Vec<char> = input.chars().collect();
+    let mut tokens = Vec::new();
+    let mut index = 0;
+
+    while index < chars.len() {
+        let c = chars[index];
+        let column = index + 1;
+        match c {
+            _ if c.is_whitespace() => index += 1,
+            '+' | '-' | '*' | '/' | '%' => {
+                tokens.push(Token { kind: TokenKind::Operator(c), column });
+                index += 1;
+            }
+            '(' => {
+                tokens.push(Token { kind: TokenKind::Open, column });
+                index += 1;
+            }
+            ')' => {
+                tokens.push(Token { kind: TokenKind::Close, column });
+                index += 1;
+            }
+            _ if c.is_ascii_digit() || c == '.' => {
+                // A literal runs on through letters (for hex digits and
+                // exponents), digits, underscores and dots; the sign of
+                // an exponent like 1e-3 belongs to it too.
+                let start = index;
+                while index < chars.len() {
+                    let c = chars[index];
+                    let exponent_sign = (c == '+' || c == '-')
+                        && matches!(chars[index - 1], 'e' | 'E')
+                        && !chars[start..].starts_with(&['0', 'x'])
+                        && !chars[start..].starts_with(&['0', 'X']);
+                    if c.is_ascii_alphanumeric() || c == '_' || c == '.' || exponent_sign {
+                        index += 1;
+                    } else {
+                        break;
+                    }
+                }
+                let literal: String = chars[start..index].iter().collect();
+                tokens.push(Token { kind: TokenKind::Literal(literal), column });
+            }
+            _ => {
+                return Err(ExprError {
+                    column,
+                    kind: ExprErrorKind::UnexpectedChar(c),
+                })
+            }
+        }
+    }
+
+    tokens.push(Token {
+        kind: TokenKind::End,
+        column: chars.len() + 1,
+    });
+    Ok(tokens)
+}
+
+/// A recursive descent parser that evaluates as it goes:
+///
+/// ```text
+/// expression = term (("+" | "-") term)*
+/// term       = factor (("*" | "/" | "%") factor)*
+/// factor     = ("-" | "+") factor | literal | "(" expression ")"
+/// ```
+///
+/// Every level of nesting is a few calls deep, so `depth` (how many
+/// factors are being parsed inside each other) is capped at MAX_DEPTH
+/// to keep something like a long run of '(' from overflowing the stack.
+struct Parser<'a> {
+    tokens: &'a [Token],
+    position: usize,
+    depth: usize,
+}
+
+/// How deeply parentheses and signs can be nested in an expression.
+const MAX_DEPTH: usize = 256;
+
+impl Parser<'_> {
+    fn peek(&self) -> &Token {
+        &self.tokens[self.position]
+    }
+
+    fn next(&mut self) -> Token {
+        let token = self.tokens[self.position].clone();
+        // The End token stays put so it can be peeked at repeatedly.
+        if token.kind != TokenKind::End {
+            self.position += 1;
+        }
+        token
+    }
+
+    fn expression<T: Number>(&mut self) -> Result<T, ExprError> {
+        let mut value = self.term::<T>()?;
+        while let TokenKind::Operator(operator @ ('+' | '-')) = self.peek().kind {
+            let column = self.next().column;
+            let rhs = self.term::<T>()?;
+            let result = if operator == '+' {
+                value.checked_add(rhs)
+            } else {
+                value.checked_sub(rhs)
+            };
+            value = result.ok_or_else(|| overflow::<T>(operator, column))?;
+        }
+        Ok(value)
+    }
+
+    fn term<T: Number>(&mut self) -> Result<T, ExprError> {
+        let mut value = self.factor::<T>()?;
+        while let TokenKind::Operator(operator @ ('*' | '/' | '%')) = self.peek().kind {
+            let column = self.next().column;
+            let rhs = self.factor::<T>()?;
+            let result = match operator {
+                '*' => value.checked_mul(rhs),
+                _ if rhs.is_zero() => {
+                    return Err(ExprError {
+                        column,
+                        kind: ExprErrorKind::DivisionByZero,
+                    })
+                }
+                '/' => value.checked_div(rhs),
+                _ => value.checked_rem(rhs),
+            };
+            value = result.ok_or_else(|| overflow::<T>(operator, column))?;
+        }
+        Ok(value)
+    }
+
+    fn factor<T: Number>(&mut self) -> Result<T, ExprError> {
+        if self.depth > MAX_DEPTH {
+            return Err(ExprError {
+                column: self.peek().column,
+                kind: ExprErrorKind::TooDeep,
+            });
+        }
+        self.depth += 1;
+        let value = self.factor_body::<T>();
+        self.depth -= 1;
+        value
+    }
+
+    /// The rest of `factor`, once it has checked the depth.
+    fn factor_body<T: Number>(&mut self) -> Result<T, ExprError> {
+        let token = self.next();
+        match token.kind {
+            TokenKind::Operator('-') => {
+                let value = self.factor::<T>()?;
+                value.checked_neg().ok_or_else(|| overflow::<T>('-', token.column))
+            }
+            TokenKind::Operator('+') => self.factor::<T>(),
+            TokenKind::Literal(literal) => literal_value(&literal, token.column),
+            TokenKind::Open => {
+                let value = self.expression::<T>()?;
+                match self.next() {
+                    Token { kind: TokenKind::Close, .. } => Ok(value),
+                    Token { kind: TokenKind::End, .. } => Err(ExprError {
+                        column: token.column,
+                        kind: ExprErrorKind::UnclosedParen,
+                    }),
+                    other => Err(ExprError {
+                        column: other.column,
+                        kind: ExprErrorKind::ExpectedOperator(other.text()),
+                    }),
+                }
+            }
+            TokenKind::End => Err(ExprError {
+                column: token.column,
+                kind: ExprErrorKind::UnexpectedEnd,
+            }),
+            _ => Err(ExprError {
+                column: token.column,
+                kind: ExprErrorKind::ExpectedNumber(token.text()),
+            }),
+        }
+    }
+}
+
+fn overflow<T: Number>(operator: char, column: usize) -> ExprError {
+    ExprError {
+        column,
+        kind: ExprErrorKind::Overflow {
+            operator,
+            type_name: T::NAME,
+        },
+    }
+}
+
+/// Read a literal such as `1_000`, `0x40` or `2.5e3`.
+fn literal_value<T: Number>(literal: &str, column: usize) -> Result<T, ExprError> {
+    let lower = literal.to_ascii_lowercase();
+    let (radix, digits) = match lower.get(..2) {
+        Some("0x") => (16, &literal[2..]),
+        Some("0o") => (8, &literal[2..]),
+        Some("0b") => (2, &literal[2..]),
+        _ => (10, literal),
+    };
+
+    // As in Rust, underscores can go between the digits (or after a
+    // prefix), but a literal can't be only underscores.
+    let digits: String = digits.chars().filter(|&c| c != '_').collect();
+    let error = |kind| ExprError { column, kind };
+    if digits.is_empty() || digits.starts_with(['+', '-']) {
+        return Err(error(ExprErrorKind::InvalidLiteral(literal.to_string())));
+    }
+
+    T::from_literal(&digits, radix).map_err(|err| {
+        error(match err {
+            LiteralError::Invalid => ExprErrorKind::InvalidLiteral(literal.to_string()),
+            LiteralError::NotWhole => ExprErrorKind::NotWhole {
+                literal: literal.to_string(),
+                type_name: T::NAME,
+            },
+            LiteralError::TooLarge => ExprErrorKind::LiteralTooLarge {
+                literal: literal.to_string(),
+                type_name: T::NAME,
+            },
+        })
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn error<T: Number>(input: &str) -> (usize, ExprErrorKind) {
+        let err = evaluate::<T>(input).unwrap_err();
+        (err.column, err.kind)
+    }
+
+    #[test]
+    fn precedence_and_associativity_match_rust() {
+        assert_eq!(evaluate::<u32>("2+3*4"), Ok(14));
+        assert_eq!(evaluate::<u32>("(2+3)*4"), Ok(20));
+        assert_eq!(evaluate::<u32>("100-20-30"), Ok(50));
+        assert_eq!(evaluate::<u32>("100/10/5"), Ok(2));
+        assert_eq!(evaluate::<u32>("17%5*2"), Ok(4));
+        assert_eq!(evaluate::<i32>("-7/2"), Ok(-3));
+        assert_eq!(evaluate::<i32>("-7%2"), Ok(-1));
+    }
+
+    #[test]
+    fn signs_and_literals() {
+        assert_eq!(evaluate::<i32>("--5"), Ok(5));
+        assert_eq!(evaluate::<i32>("-(2+3)"), Ok(-5));
+        assert_eq!(evaluate::<i32>("+4"), Ok(4));
+        assert_eq!(evaluate::<i32>("3*-2"), Ok(-6));
+        assert_eq!(evaluate::<u32>("0x40 + 0o10 + 0b11 + 1_000"), Ok(1075));
+        assert_eq!(evaluate::<f64>("2.5e3/1e-1"), Ok(25000.0));
+        // 128 doesn't fit in an i8 before it is negated, so i8::MIN
+        // has to be written as -127-1.
+        assert_eq!(evaluate::<i8>("-127-1"), Ok(i8::MIN));
+    }
+
+    #[test]
+    fn every_error_has_its_column() {
+        assert_eq!(error::<u32>("5 $ 3"), (3, ExprErrorKind::UnexpectedChar('$')));
+        assert_eq!(error::<u32>("5+*3"), (3, ExprErrorKind::ExpectedNumber(String::from("*"))));
+        assert_eq!(error::<u32>("5 3"), (3, ExprErrorKind::ExpectedOperator(String::from("3"))));
+        assert_eq!(error::<u32>("(5 3)"), (4, ExprErrorKind::ExpectedOperator(String::from("3"))));
+        assert_eq!(error::<u32>("5+"), (3, ExprErrorKind::UnexpectedEnd));
+        assert_eq!(error::<u32>("2*(5+1"), (3, ExprErrorKind::UnclosedParen));
+        assert_eq!(error::<u32>("5+1)"), (4, ExprErrorKind::UnmatchedParen));
+        assert_eq!(error::<u32>("1+0xg"), (3, ExprErrorKind::InvalidLiteral(String::from("0xg"))));
+        assert_eq!(error::<u32>("1+0x_"), (3, ExprErrorKind::InvalidLiteral(String::from("0x_"))));
+        assert_eq!(
+            error::<u32>("1+2.5"),
+            (3, ExprErrorKind::NotWhole { literal: String::from("2.5"), type_name: "u32" })
+        );
+        assert_eq!(
+            error::<u8>("1+300"),
+            (3, ExprErrorKind::LiteralTooLarge { literal: String::from("300"), type_name: "u8" })
+        );
+        assert_eq!(
+            error::<u8>("200+100"),
+            (4, ExprErrorKind::Overflow { operator: '+', type_name: "u8" })
+        );
+        assert_eq!(error::<u32>("1-2"), (2, ExprErrorKind::Overflow { operator: '-', type_name: "u32" }));
+        assert_eq!(error::<i8>("-(-127-1)"), (1, ExprErrorKind::Overflow { operator: '-', type_name: "i8" }));
+        assert_eq!(
+            error::<f32>("1e30*1e30"),
+            (5, ExprErrorKind::Overflow { operator: '*', type_name: "f32" })
+        );
+        assert_eq!(error::<u32>("7/(3-3)"), (2, ExprErrorKind::DivisionByZero));
+        assert_eq!(error::<u32>("7%0"), (2, ExprErrorKind::DivisionByZero));
+    }
+
+    #[test]
+    fn nesting_is_limited() {
+        let deepest = format!("{}5{}", "(".repeat(MAX_DEPTH), ")".repeat(MAX_DEPTH));
+        assert_eq!(evaluate::<u32>(&deepest), Ok(5));
+
+        let too_deep = format!("{}5{}", "(".repeat(MAX_DEPTH + 1), ")".repeat(MAX_DEPTH + 1));
+        assert_eq!(error::<u32>(&too_deep), (MAX_DEPTH + 2, ExprErrorKind::TooDeep));
+
+        // Far past the limit, and with signs, it is an error rather
+        // than a stack overflow.
+        assert_eq!(error::<u32>(&"(".repeat(200_000)).1, ExprErrorKind::TooDeep);
+        assert_eq!(error::<i32>(&format!("{}1", "-".repeat(200_000))).1, ExprErrorKind::TooDeep);
+    }
+
+    #[test]
+    fn the_pointer_sits_under_the_column() {
+        let err = evaluate::<u32>("12 + x").unwrap_err();
+        assert_eq!(err.pointer("12 + x"), "12 + x\n     ^");
+        assert_eq!(err.to_string(), "column 6: unexpected 'x'");
+    }
+
+    #[test]
+    fn only_operators_and_prefixes_look_like_expressions() {
+        assert!(looks_like_expression("50+25"));
+        assert!(looks_like_expression("0x40"));
+        assert!(looks_like_expression("1_000"));
+        assert!(!looks_like_expression("abc"));
+        assert!(!looks_like_expression("42"));
+    }
+}
@@ -0,0 +1,173 @@
+use crate::game::{Game, Outcome};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// How the game answers a wrong guess.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FeedbackMode {
+    /// "Too small!" or "Too big!", straight from the Ordering.
+    Ordering,
+    /// How close the guess is ("freezing" to "burning") and whether it
+    /// is closer than the last one, without saying which direction.
+    HotCold,
+}
+
+impl FeedbackMode {
+    pub fn from_name(name: &str) -> Option<FeedbackMode> {
+        match name.to_ascii_lowercase().as_str() {
+            "ordering" => Some(FeedbackMode::Ordering),
+            "hotcold" | "hot-cold" => Some(FeedbackMode::HotCold),
+            _ => None,
+        }
+    }
+}
+
+/// How close a guess is to the secret, relative to the size of the
+/// range.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Temperature {
+    Freezing,
+    Cold,
+    Warm,
+    Hot,
+    Burning,
+}
+
+impl Temperature {
+    /// Bucket a wrong guess by how far it is from the secret, as a
+    /// fraction of the range: within 2% (or off by one) is burning,
+    /// within 5% hot, within 15% warm, within 35% cold, and anything
+    /// further away freezing.
+    pub fn from_distance(distance: u64, range_size: u64) -> Temperature {
+        let fraction = distance as f64 / range_size as f64;
+
+        if distance <= 1 || fraction <= 0.02 {
+            Temperature::Burning
+        } else if fraction <= 0.05 {
+            Temperature::Hot
+        } else if fraction <= 0.15 {
+            Temperature::Warm
+        } else if fraction <= 0.35 {
+            Temperature::Cold
+        } else {
+            Temperature::Freezing
+        }
+    }
+}
+
+impl fmt::Display for Temperature {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let text = match self {
+            Temperature::Freezing => "Freezing!",
+            Temperature::Cold => "Cold.",
+            Temperature::Warm => "Warm.",
+            Temperature::Hot => "Hot!",
+            Temperature::Burning => "Burning!",
+        };
+        write!(f, "{text}")
+    }
+}
+
+/// How a guess compares with the one before it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Trend {
+    Warmer,
+    Colder,
+    Same,
+}
+
+/// Hot/cold feedback for the most recent guess in `game`, or None if
+/// nothing has been guessed yet.
+pub fn hot_cold(game: &Game) -> Option<(Temperature, Option<Trend>)> {
+    let distance = |guess: u32| u64::from(guess.abs_diff(game.secret()));
+
+    let (&last, earlier) = game.history().split_last()?;
+    let temperature = Temperature::from_distance(distance(last), game.range_size());
+    let trend = earlier.last().map(|&previous| {
+        match distance(last).cmp(&distance(previous)) {
+            std::cmp::Ordering::Less => Trend::Warmer,
+            std::cmp::Ordering::Greater => Trend::Colder,
+            std::cmp::Ordering::Equal => Trend::Same,
+        }
+    });
+
+    Some((temperature, trend))
+}
+
+/// The line to print after `outcome`, the latest guess in `game`.
+pub fn describe(mode: FeedbackMode, game: &Game, outcome: &Outcome) -> String {
+    if mode == FeedbackMode::Ordering || outcome.is_win() {
+        return outcome.message().to_string();
+    }
+
+    match hot_cold(game) {
+        Some((temperature, Some(Trend::Warmer))) => {
+            format!("{temperature} Warmer than your last guess.")
+        }
+        Some((temperature, Some(Trend::Colder))) => {
+            format!("{temperature} Colder than your last guess.")
+        }
+        Some((temperature, Some(Trend::Same))) => {
+            format!("{temperature} Just as close as your last guess.")
+        }
+        Some((temperature, None)) => temperature.to_string(),
+        None => outcome.message().to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn each_temperature_ends_at_its_threshold() {
+        // In a range of 100, every number of distance is one percent.
+        let temperature = |distance| Temperature::from_distance(distance, 100);
+        assert_eq!(temperature(2), Temperature::Burning);
+        assert_eq!(temperature(3), Temperature::Hot);
+        assert_eq!(temperature(5), Temperature::Hot);
+        assert_eq!(temperature(6), Temperature::Warm);
+        assert_eq!(temperature(15), Temperature::Warm);
+        assert_eq!(temperature(16), Temperature::Cold);
+        assert_eq!(temperature(35), Temperature::Cold);
+        assert_eq!(temperature(36), Temperature::Freezing);
+        assert_eq!(temperature(99), Temperature::Freezing);
+    }
+
+    #[test]
+    fn off_by_one_is_always_burning() {
+        assert_eq!(Temperature::from_distance(1, 10), Temperature::Burning);
+        // Two off is a fifth of this range, so only one off is burning.
+        assert_eq!(Temperature::from_distance(2, 10), Temperature::Cold);
+        assert_eq!(Temperature::from_distance(1, 2), Temperature::Burning);
+        assert_eq!(Temperature::from_distance(1, 1 << 32), Temperature::Burning);
+        assert_eq!(Temperature::from_distance(u64::from(u32::MAX), 1 << 32), Temperature::Freezing);
+    }
+
+    #[test]
+    fn temperatures_are_ordered_from_coldest() {
+        assert!(Temperature::Freezing < Temperature::Cold);
+        assert!(Temperature::Hot < Temperature::Burning);
+    }
+
+    #[test]
+    fn each_guess_is_compared_with_the_last() {
+        let mut game = Game::with_secret(1, 100, 50);
+        assert_eq!(hot_cold(&game), None);
+
+        game.submit_guess(10);
+        assert_eq!(hot_cold(&game), Some((Temperature::Freezing, None)));
+        game.submit_guess(60);
+        assert_eq!(hot_cold(&game), Some((Temperature::Warm, Some(Trend::Warmer))));
+        game.submit_guess(40);
+        assert_eq!(hot_cold(&game), Some((Temperature::Warm, Some(Trend::Same))));
+        let outcome = game.submit_guess(99);
+        assert_eq!(hot_cold(&game), Some((Temperature::Freezing, Some(Trend::Colder))));
+        assert_eq!(describe(FeedbackMode::HotCold, &game, &outcome), "Freezing! Colder than your last guess.");
+        assert_eq!(describe(FeedbackMode::Ordering, &game, &outcome), "Too big!");
+
+        let outcome = game.submit_guess(50);
+        assert_eq!(describe(FeedbackMode::HotCold, &game, &outcome), "You win!");
+    }
+}
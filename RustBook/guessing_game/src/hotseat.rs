@@ -0,0 +1,228 @@
+use crate::game::{Game, Outcome};
+use crate::secret::SecretSource;
+use rand::RngCore;
+
+pub const MIN_PLAYERS: usize = 2;
+pub const MAX_PLAYERS: usize = 8;
+
+/// One person taking turns at the keyboard, with their own Game so
+/// their attempts and history are counted separately.
+#[derive(Debug, Clone)]
+pub struct Player {
+    pub name: String,
+    pub game: Game,
+}
+
+/// Several players taking turns at the same terminal.
+///
+/// With a shared secret, every player's Game holds the same secret
+/// number (from a single draw) and the first to find it wins. With
+/// separate secrets, everyone plays their own game in turn and the
+/// winner is whoever found their secret in the fewest guesses.
+#[derive(Debug, Clone)]
+pub struct HotSeat {
+    players: Vec<Player>,
+    turn: usize,
+    shared_secret: bool,
+}
+
+/// A player's place in the final standings.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Standing {
+    pub rank: usize,
+    pub name: String,
+    pub won: bool,
+    pub attempts: usize,
+}
+
+impl HotSeat {
+    /// Everyone guesses the same secret, drawn once from `source`.
+    pub fn shared(
+        names: &[String],
+        source: &dyn SecretSource,
+        max_attempts: Option<usize>,
+        rng: &mut dyn RngCore,
+    ) -> HotSeat {
+        let secret = source.draw(rng);
+        HotSeat::new(names, true, |_| {
+            Game::with_secret(source.low(), source.high(), secret).with_max_attempts(max_attempts)
+        })
+    }
+
+    /// Every player gets their own secret, drawn from `source` in turn
+    /// order.
+    pub fn separate(
+        names: &[String],
+        source: &dyn SecretSource,
+        max_attempts: Option<usize>,
+        rng: &mut dyn RngCore,
+    ) -> HotSeat {
+        HotSeat::new(names, false, |_| {
+            Game::from_source(source, rng).with_max_attempts(max_attempts)
+        })
+    }
+
+    fn new(names: &[String], shared_secret: bool, mut new_game: impl FnMut(usize) -> Game) -> HotSeat {
+        let players = names
+            .iter()
+            .enumerate()
+            .map(|(index, name)| Player {
+                name: name.clone(),
+                game: new_game(index),
+            })
+            .collect();
+
+        HotSeat {
+            players,
+            turn: 0,
+            shared_secret,
+        }
+    }
+
+    pub fn players(&self) -> &[Player] {
+        &self.players
+    }
+
+    pub fn is_shared_secret(&self) -> bool {
+        self.shared_secret
+    }
+
+    /// The player whose turn it is.
+    pub fn current(&self) -> &Player {
+        &self.players[self.turn]
+    }
+
+    /// The index into `players` of the player whose turn it is.
+    pub fn current_index(&self) -> usize {
+        self.turn
+    }
+
+    /// Submit the current player's guess, then pass the turn on to the
+    /// next player who is still playing.
+    pub fn submit_guess(&mut self, guess: u32) -> Outcome {
+        let outcome = self.players[self.turn].game.submit_guess(guess);
+
+        if !self.is_over() {
+            for step in 1..=self.players.len() {
+                let next = (self.turn + step) % self.players.len();
+                if !self.players[next].game.is_over() {
+                    self.turn = next;
+                    break;
+                }
+            }
+        }
+
+        outcome
+    }
+
+    /// With a shared secret the game is over as soon as anyone finds
+    /// it; otherwise it lasts until every player has won or run out of
+    /// attempts.
+    pub fn is_over(&self) -> bool {
+        let anyone_won = self.players.iter().any(|player| player.game.is_won());
+        let everyone_done = self.players.iter().all(|player| player.game.is_over());
+        everyone_done || (self.shared_secret && anyone_won)
+    }
+
+    /// Players who found the secret come first, fewest attempts first.
+    /// Everyone else follows, in turn order.
+    pub fn standings(&self) -> Vec<Standing> {
+        let mut players: Vec<&Player> = self.players.iter().collect();
+        // Only winners are sorted by attempts. sort_by_key is stable,
+        // so ties, and everyone who didn't win, keep their turn order.
+        players.sort_by_key(|player| {
+            let won = player.game.is_won();
+            (!won, if won { player.game.attempts() } else { 0 })
+        });
+
+        let mut standings: Vec<Standing> = Vec::new();
+        for player in players {
+            let won = player.game.is_won();
+            let rank = match standings.last() {
+                // Players who tie share a rank.
+                Some(last) if last.won == won && (!won || last.attempts == player.game.attempts()) => last.rank,
+                _ => standings.len() + 1,
+            };
+            standings.push(Standing {
+                rank,
+                name: player.name.clone(),
+                won,
+                attempts: player.game.attempts(),
+            });
+        }
+        standings
+    }
+
+    /// The final standings as a table.
+    pub fn render_standings(&self) -> String {
+        let mut out = format!("{:>4}  {:<16} {:<6} {:>8}\n", "#", "name", "result", "attempts");
+        for standing in self.standings() {
+            out.push_str(&format!(
+                "{:>4}  {:<16} {:<6} {:>8}\n",
+                standing.rank,
+                standing.name,
+                if standing.won { "won" } else { "-" },
+                standing.attempts
+            ));
+        }
+        out
+    }
+}
+
+/// Read the --players value: either a count ("3" becomes Player 1,
+/// Player 2 and Player 3) or a comma separated list of names.
+pub fn parse_players(value: &str) -> Result<Vec<String>, String> {
+    // The number of players is checked before any names are made, so
+    // "--players 1000000000000" is an error rather than a huge list.
+    let numbered = value.trim().parse::<usize>().ok();
+    let count = numbered.unwrap_or_else(|| value.split(',').count());
+    if !(MIN_PLAYERS..=MAX_PLAYERS).contains(&count) {
+        return Err(format!("hot-seat games need {MIN_PLAYERS} to {MAX_PLAYERS} players, not {count}"));
+    }
+
+    let names: Vec<String> = match numbered {
+        Some(count) => (1..=count).map(|n| format!("Player {n}")).collect(),
+        None => value.split(',').map(|name| name.trim().to_string()).collect(),
+    };
+    if names.iter().any(|name| name.is_empty()) {
+        return Err(String::from("player names can't be empty"));
+    }
+    for (index, name) in names.iter().enumerate() {
+        if names[..index].contains(name) {
+            return Err(format!("two players are called '{name}'"));
+        }
+    }
+
+    Ok(names)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn names(names: &[&str]) -> Vec<String> {
+        names.iter().map(|name| name.to_string()).collect()
+    }
+
+    #[test]
+    fn players_who_did_not_win_keep_their_turn_order() {
+        let players = names(&["ann", "bob", "cy", "dee"]);
+        let mut hot_seat = HotSeat::new(&players, true, |_| Game::with_secret(1, 100, 50));
+        // Ann guesses twice before Bob finds the secret; Cy and Dee
+        // only once.
+        for guess in [10, 20, 30, 40, 60, 50] {
+            hot_seat.submit_guess(guess);
+        }
+        assert!(hot_seat.is_over());
+
+        let standings = hot_seat.standings();
+        let standings: Vec<(usize, &str, bool, usize)> = standings
+            .iter()
+            .map(|standing| (standing.rank, standing.name.as_str(), standing.won, standing.attempts))
+            .collect();
+        assert_eq!(
+            standings,
+            [(1, "bob", true, 2), (2, "ann", false, 2), (2, "cy", false, 1), (2, "dee", false, 1)]
+        );
+    }
+}
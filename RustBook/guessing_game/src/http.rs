@@ -0,0 +1,637 @@
+use crate::config::{validate_range, Difficulty};
+use crate::game::{optimal_attempts, range_size, seeded_rng, Game};
+use crate::protocol::{self, ErrorCode};
+use crate::secret::Distribution;
+use crate::transcript::Comparison;
+use rand::rngs::StdRng;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// The address the HTTP API listens on by default. Only localhost: the
+/// API has no authentication.
+pub const DEFAULT_ADDRESS: &str = "127.0.0.1:8080";
+
+/// How long a game is kept after its last request, unless
+/// --session-timeout says otherwise.
+pub const DEFAULT_SESSION_TIMEOUT: Duration = Duration::from_secs(10 * 60);
+
+/// How many connections are served at once unless the settings say
+/// otherwise. Each has its own thread, so this also bounds the threads.
+pub const DEFAULT_MAX_CONNECTIONS: usize = 64;
+
+/// How many games are kept at once unless the settings say otherwise.
+pub const DEFAULT_MAX_GAMES: usize = 10_000;
+
+/// How long a kept-alive connection may sit idle before it is closed,
+/// so idle clients don't hold on to a thread forever.
+const IDLE_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// The longest request line or header line read, and the most headers.
+const MAX_LINE: u64 = 8 * 1024;
+const MAX_HEADERS: usize = 100;
+
+/// The biggest request body read. Game options and guesses are tiny.
+const MAX_BODY: usize = 64 * 1024;
+
+/// What games on the server are like unless the request for a new game
+/// says otherwise.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ApiSettings {
+    pub low: u32,
+    pub high: u32,
+    /// The preset the range came from, or None for a custom range.
+    pub difficulty: Option<Difficulty>,
+    pub max_attempts: Option<usize>,
+    /// How the secrets are drawn.
+    pub secret: Distribution,
+    /// Seed for the secrets of games that don't bring their own seed.
+    /// Game ids never come from it, so they can't be worked out from
+    /// the seed.
+    pub seed: Option<u64>,
+    /// Games are forgotten once nobody has asked about them for this
+    /// long.
+    pub session_timeout: Duration,
+    /// Connections beyond this many are answered with 503 Service
+    /// Unavailable and closed.
+    pub max_connections: usize,
+    /// While there are this many games, new ones are refused with 503
+    /// Service Unavailable.
+    pub max_games: usize,
+}
+
+/// The body of `POST /games`. Every field is optional, and an empty
+/// body plays with the server's settings.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct GameOptions {
+    pub difficulty: Option<Difficulty>,
+    pub min: Option<u32>,
+    pub max: Option<u32>,
+    pub attempts: Option<Attempts>,
+    /// Seed for this game's secret, so the same seed always picks the
+    /// same secret.
+    pub seed: Option<u64>,
+    /// How the secret is drawn, as given to --secret.
+    pub secret: Option<String>,
+}
+
+/// How many guesses a new game gets: a number, or "unlimited".
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(untagged)]
+pub enum Attempts {
+    Limit(usize),
+    Unlimited(Unlimited),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Unlimited {
+    Unlimited,
+}
+
+/// Whether a game can still be played.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Status {
+    Playing,
+    Won,
+    Lost,
+}
+
+/// A game as `GET /games/{id}` shows it.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GameState {
+    pub id: String,
+    pub low: u32,
+    pub high: u32,
+    pub difficulty: Option<Difficulty>,
+    pub max_attempts: Option<usize>,
+    pub attempts: usize,
+    pub remaining: Option<usize>,
+    pub guesses: Vec<GuessRecord>,
+    pub status: Status,
+    /// The secret, once the game is over.
+    pub secret: Option<u32>,
+    /// Seconds until the game is forgotten, unless it is asked about
+    /// again.
+    pub expires_in: f64,
+}
+
+/// A guess made in a game, and how it compared with the secret.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GuessRecord {
+    pub guess: u32,
+    pub result: Comparison,
+}
+
+/// The answer to `POST /games/{id}/guesses`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GuessReply {
+    pub guess: u32,
+    /// "less" if the guess is smaller than the secret, "greater" if it
+    /// is bigger, "equal" if it is the secret.
+    pub result: Comparison,
+    /// How many guesses have been made, counting this one.
+    pub attempt: usize,
+    pub remaining: Option<usize>,
+    pub status: Status,
+    /// The secret, if this guess ended the game.
+    pub secret: Option<u32>,
+}
+
+/// The body of every response that isn't a success.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ApiError {
+    pub code: ErrorCode,
+    pub message: String,
+}
+
+/// Serve the guessing game as a JSON API over HTTP, one thread per
+/// connection, until the listener fails.
+///
+/// - `POST /games` starts a game, with `GameOptions` as the body, and
+///   answers 201 Created with its `GameState` and a Location header.
+/// - `POST /games/{id}/guesses` takes `{"guess": n}` and answers with a
+///   `GuessReply`: the guess compared with the secret, like the
+///   `Ordering` in the line-by-line game.
+/// - `GET /games/{id}` shows the game's `GameState`.
+///
+/// Mistakes get a 4xx status and an `ApiError`. Every game lives on
+/// the server until nobody has asked about it for the session timeout,
+/// and up to `max_connections` clients can play (even the same game)
+/// at once.
+pub fn serve(listener: TcpListener, settings: ApiSettings) -> io::Result<()> {
+    settings
+        .secret
+        .source(settings.low, settings.high)
+        .map_err(|err| io::Error::new(io::ErrorKind::InvalidInput, err))?;
+    let max_connections = settings.max_connections;
+    let sessions = Arc::new(Mutex::new(Sessions {
+        games: HashMap::new(),
+        rng: seeded_rng(settings.seed),
+        settings,
+    }));
+    let open = Arc::new(AtomicUsize::new(0));
+
+    for stream in listener.incoming() {
+        let mut stream = stream?;
+        if open.fetch_add(1, Ordering::SeqCst) >= max_connections {
+            open.fetch_sub(1, Ordering::SeqCst);
+            // The answer is small enough to fit in the socket's buffer,
+            // so writing it doesn't hold up the next connection.
+            let response = Response::error(
+                503,
+                ErrorCode::Busy,
+                format!("the server is already serving {max_connections} connections; try again later"),
+            );
+            let _ = write_response(&mut stream, &response, false);
+            continue;
+        }
+
+        let connection = OpenConnection(Arc::clone(&open));
+        let sessions = Arc::clone(&sessions);
+        thread::spawn(move || {
+            // A client that goes away halfway through only affects its
+            // own connection.
+            let _ = handle_connection(stream, &sessions);
+            drop(connection);
+        });
+    }
+
+    Ok(())
+}
+
+/// Counts a connection as open until its thread is done with it.
+struct OpenConnection(Arc<AtomicUsize>);
+
+impl Drop for OpenConnection {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+/// Every game on the server, by id.
+struct Sessions {
+    games: HashMap<String, Session>,
+    rng: StdRng,
+    settings: ApiSettings,
+}
+
+struct Session {
+    game: Game,
+    difficulty: Option<Difficulty>,
+    last_used: Instant,
+}
+
+impl Sessions {
+    /// Forget the games nobody has asked about for the session timeout.
+    fn expire(&mut self) {
+        let timeout = self.settings.session_timeout;
+        self.games.retain(|_, session| session.last_used.elapsed() < timeout);
+    }
+
+    /// Start a game with `options` and return its id.
+    fn create(&mut self, options: GameOptions) -> Result<String, String> {
+        let settings = &self.settings;
+        let (mut low, mut high, mut difficulty) = match options.difficulty {
+            Some(difficulty) => {
+                let (low, high) = difficulty.bounds();
+                (low, high, Some(difficulty))
+            }
+            None => (settings.low, settings.high, settings.difficulty),
+        };
+        if options.min.is_some() || options.max.is_some() {
+            low = options.min.unwrap_or(low);
+            high = options.max.unwrap_or(high);
+            difficulty = None;
+        }
+        validate_range(low, high)?;
+
+        let distribution = match &options.secret {
+            Some(name) => Distribution::from_name(name)?,
+            None => settings.secret.clone(),
+        };
+        let source = distribution.source(low, high)?;
+        // Like on the command line, a game gets as many guesses as a
+        // binary search needs unless it says otherwise.
+        let max_attempts = match options.attempts {
+            Some(Attempts::Limit(0)) => return Err(String::from("attempts must be at least 1")),
+            Some(Attempts::Limit(attempts)) => Some(attempts),
+            Some(Attempts::Unlimited(_)) => None,
+            None if (low, high) == (settings.low, settings.high) => settings.max_attempts,
+            None => Some(optimal_attempts(range_size(low, high))),
+        };
+
+        let game = match options.seed {
+            Some(seed) => Game::from_source(source.as_ref(), &mut seeded_rng(Some(seed))),
+            None => Game::from_source(source.as_ref(), &mut self.rng),
+        }
+        .with_max_attempts(max_attempts);
+
+        // Anyone with a game's id can play it, so ids come from
+        // thread_rng, which the operating system seeds, rather than
+        // from the seeded rng.
+        let id = loop {
+            let id = format!("{:016x}", rand::thread_rng().gen::<u64>());
+            if !self.games.contains_key(&id) {
+                break id;
+            }
+        };
+        self.games.insert(
+            id.clone(),
+            Session {
+                game,
+                difficulty,
+                last_used: Instant::now(),
+            },
+        );
+        Ok(id)
+    }
+
+    /// The game with this id, which counts as a use of it.
+    fn get(&mut self, id: &str) -> Option<&mut Session> {
+        let session = self.games.get_mut(id)?;
+        session.last_used = Instant::now();
+        Some(session)
+    }
+
+    fn state(&self, id: &str) -> Option<GameState> {
+        let session = self.games.get(id)?;
+        let game = &session.game;
+        let guesses = game
+            .history()
+            .iter()
+            .map(|&guess| GuessRecord {
+                guess,
+                result: guess.cmp(&game.secret()).into(),
+            })
+            .collect();
+        let expires_in = self
+            .settings
+            .session_timeout
+            .saturating_sub(session.last_used.elapsed());
+
+        Some(GameState {
+            id: id.to_string(),
+            low: game.low(),
+            high: game.high(),
+            difficulty: session.difficulty,
+            max_attempts: game.max_attempts(),
+            attempts: game.attempts(),
+            remaining: game.remaining(),
+            guesses,
+            status: status(game),
+            secret: game.is_over().then(|| game.secret()),
+            expires_in: expires_in.as_secs_f64(),
+        })
+    }
+}
+
+fn status(game: &Game) -> Status {
+    if game.is_won() {
+        Status::Won
+    } else if game.is_lost() {
+        Status::Lost
+    } else {
+        Status::Playing
+    }
+}
+
+/// A request read off a connection.
+struct Request {
+    method: String,
+    path: String,
+    body: Vec<u8>,
+    /// Whether the client wants to send more requests on the same
+    /// connection.
+    keep_alive: bool,
+}
+
+/// What came in on a connection.
+enum Incoming {
+    Request(Request),
+    /// Something that isn't a request we can answer; the response says
+    /// why, and the connection is closed after it.
+    Malformed(Response),
+    /// The client closed the connection.
+    Closed,
+}
+
+struct Response {
+    status: u16,
+    /// Headers besides Content-Type, Content-Length and Connection.
+    headers: Vec<(&'static str, String)>,
+    body: String,
+}
+
+impl Response {
+    fn json(status: u16, body: &impl Serialize) -> Response {
+        Response {
+            status,
+            headers: Vec::new(),
+            body: serde_json::to_string(body).expect("API responses always serialize"),
+        }
+    }
+
+    fn error(status: u16, code: ErrorCode, message: impl Into<String>) -> Response {
+        Response::json(
+            status,
+            &ApiError {
+                code,
+                message: message.into(),
+            },
+        )
+    }
+
+    fn not_found(message: impl Into<String>) -> Response {
+        Response::error(404, ErrorCode::NotFound, message)
+    }
+
+    fn method_not_allowed(allow: &'static str) -> Response {
+        let mut response = Response::error(
+            405,
+            ErrorCode::MethodNotAllowed,
+            format!("this page only takes {allow}"),
+        );
+        response.headers.push(("Allow", allow.to_string()));
+        response
+    }
+}
+
+/// Answer requests on one connection until the client closes it, asks
+/// to close it, goes quiet for IDLE_TIMEOUT, or sends something that
+/// isn't HTTP.
+fn handle_connection(stream: TcpStream, sessions: &Mutex<Sessions>) -> io::Result<()> {
+    stream.set_read_timeout(Some(IDLE_TIMEOUT))?;
+    let mut reader = BufReader::new(stream.try_clone()?);
+    let mut writer = stream;
+
+    loop {
+        match read_request(&mut reader)? {
+            Incoming::Request(request) => {
+                let response = route(&request, sessions);
+                write_response(&mut writer, &response, request.keep_alive)?;
+                if !request.keep_alive {
+                    return Ok(());
+                }
+            }
+            Incoming::Malformed(response) => return write_response(&mut writer, &response, false),
+            Incoming::Closed => return Ok(()),
+        }
+    }
+}
+
+/// Read one HTTP/1.x request: the request line, the headers, and a
+/// body of Content-Length bytes.
+fn read_request(reader: &mut impl BufRead) -> io::Result<Incoming> {
+    let bad_request = |message: &str| Ok(Incoming::Malformed(Response::error(400, ErrorCode::InvalidRequest, message)));
+
+    let Some(request_line) = read_line(reader)? else {
+        return Ok(Incoming::Closed);
+    };
+    let mut parts = request_line.split_whitespace();
+    let (Some(method), Some(path), Some(version), None) = (parts.next(), parts.next(), parts.next(), parts.next())
+    else {
+        return bad_request("the request line should look like \"GET /games/{id} HTTP/1.1\"");
+    };
+    if !version.starts_with("HTTP/1.") {
+        return bad_request("only HTTP/1.0 and HTTP/1.1 are spoken here");
+    }
+    // HTTP/1.1 keeps connections open unless asked not to, 1.0 closes
+    // them unless asked not to.
+    let mut keep_alive = version != "HTTP/1.0";
+    let mut content_length = 0;
+
+    let mut headers = 0;
+    loop {
+        let Some(line) = read_line(reader)? else {
+            return Ok(Incoming::Closed);
+        };
+        if line.is_empty() {
+            break;
+        }
+        headers += 1;
+        if headers > MAX_HEADERS {
+            return bad_request("too many headers");
+        }
+        let Some((name, value)) = line.split_once(':') else {
+            return bad_request("a header should look like \"Name: value\"");
+        };
+        let value = value.trim();
+        if name.eq_ignore_ascii_case("content-length") {
+            let Ok(length) = value.parse() else {
+                return bad_request("Content-Length should be a number");
+            };
+            content_length = length;
+        } else if name.eq_ignore_ascii_case("transfer-encoding") {
+            return bad_request("chunked bodies aren't supported; send Content-Length");
+        } else if name.eq_ignore_ascii_case("connection") {
+            keep_alive = match value.to_ascii_lowercase().as_str() {
+                "close" => false,
+                "keep-alive" => true,
+                _ => keep_alive,
+            };
+        }
+    }
+
+    if content_length > MAX_BODY {
+        return Ok(Incoming::Malformed(Response::error(
+            413,
+            ErrorCode::TooLarge,
+            format!("request bodies can be at most {MAX_BODY} bytes"),
+        )));
+    }
+    let mut body = vec![0; content_length];
+    reader.read_exact(&mut body)?;
+
+    Ok(Incoming::Request(Request {
+        method: method.to_string(),
+        path: path.to_string(),
+        body,
+        keep_alive,
+    }))
+}
+
+/// One line of the request head without its line ending, or None if
+/// the connection was closed first. Lines longer than MAX_LINE, or that
+/// aren't UTF-8, are errors.
+fn read_line(reader: &mut impl BufRead) -> io::Result<Option<String>> {
+    let mut line = String::new();
+    if reader.by_ref().take(MAX_LINE).read_line(&mut line)? == 0 {
+        return Ok(None);
+    }
+    if !line.ends_with('\n') {
+        return Err(io::Error::new(io::ErrorKind::InvalidData, "request line too long"));
+    }
+    Ok(Some(line.trim_end_matches(['\r', '\n']).to_string()))
+}
+
+fn write_response(writer: &mut impl Write, response: &Response, keep_alive: bool) -> io::Result<()> {
+    let mut head = format!(
+        "HTTP/1.1 {} {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: {}\r\n",
+        response.status,
+        reason(response.status),
+        response.body.len(),
+        if keep_alive { "keep-alive" } else { "close" }
+    );
+    for (name, value) in &response.headers {
+        head.push_str(&format!("{name}: {value}\r\n"));
+    }
+    head.push_str("\r\n");
+
+    writer.write_all(head.as_bytes())?;
+    writer.write_all(response.body.as_bytes())?;
+    writer.flush()
+}
+
+fn reason(status: u16) -> &'static str {
+    match status {
+        200 => "OK",
+        201 => "Created",
+        400 => "Bad Request",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        409 => "Conflict",
+        413 => "Payload Too Large",
+        503 => "Service Unavailable",
+        _ => "Unknown",
+    }
+}
+
+/// Send a request to the handler for its page.
+fn route(request: &Request, sessions: &Mutex<Sessions>) -> Response {
+    let path = request.path.split('?').next().unwrap_or_default();
+    let segments: Vec<&str> = path.trim_matches('/').split('/').collect();
+    let Ok(body) = std::str::from_utf8(&request.body) else {
+        return Response::error(400, ErrorCode::InvalidJson, "the body is not valid UTF-8");
+    };
+
+    let mut sessions = sessions.lock().unwrap();
+    sessions.expire();
+    match (request.method.as_str(), segments.as_slice()) {
+        ("POST", ["games"]) => create_game(&mut sessions, body),
+        ("POST", ["games", id, "guesses"]) => submit_guess(&mut sessions, id, body),
+        ("GET", ["games", id]) => show_game(&mut sessions, id),
+        (_, ["games"]) | (_, ["games", _, "guesses"]) => Response::method_not_allowed("POST"),
+        (_, ["games", _]) => Response::method_not_allowed("GET"),
+        _ => Response::not_found(format!("there is nothing at {path}")),
+    }
+}
+
+fn create_game(sessions: &mut Sessions, body: &str) -> Response {
+    let max_games = sessions.settings.max_games;
+    if sessions.games.len() >= max_games {
+        return Response::error(
+            503,
+            ErrorCode::Busy,
+            format!("the server already has {max_games} games; try again once some have expired"),
+        );
+    }
+
+    let options = if body.trim().is_empty() {
+        GameOptions::default()
+    } else {
+        match serde_json::from_str(body) {
+            Ok(options) => options,
+            Err(err) if err.is_data() => return Response::error(400, ErrorCode::InvalidOptions, err.to_string()),
+            Err(err) => return Response::error(400, ErrorCode::InvalidJson, err.to_string()),
+        }
+    };
+
+    match sessions.create(options) {
+        Ok(id) => {
+            let state = sessions.state(&id).expect("the game was just created");
+            let mut response = Response::json(201, &state);
+            response.headers.push(("Location", format!("/games/{id}")));
+            response
+        }
+        Err(err) => Response::error(400, ErrorCode::InvalidOptions, err),
+    }
+}
+
+fn submit_guess(sessions: &mut Sessions, id: &str, body: &str) -> Response {
+    let Some(session) = sessions.get(id) else {
+        return no_such_game(id);
+    };
+    let game = &mut session.game;
+    if game.is_over() {
+        return Response::error(409, ErrorCode::GameOver, format!("game {id} is already over"));
+    }
+
+    // The body is read like a line of the JSON-lines protocol, so the
+    // two answer bad guesses the same way.
+    let guess = match protocol::read_guess(body, game.low(), game.high()) {
+        Ok(guess) => guess,
+        Err((code, message)) => return Response::error(400, code, message),
+    };
+    let outcome = game.submit_guess(guess);
+    Response::json(
+        200,
+        &GuessReply {
+            guess,
+            result: outcome.ordering.into(),
+            attempt: outcome.attempts,
+            remaining: outcome.remaining,
+            status: status(game),
+            secret: game.is_over().then(|| game.secret()),
+        },
+    )
+}
+
+fn show_game(sessions: &mut Sessions, id: &str) -> Response {
+    if sessions.get(id).is_none() {
+        return no_such_game(id);
+    }
+    Response::json(200, &sessions.state(id).expect("the game was just found"))
+}
+
+fn no_such_game(id: &str) -> Response {
+    Response::not_found(format!("there is no game {id}; it may have expired"))
+}
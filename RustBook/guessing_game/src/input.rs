@@ -0,0 +1,216 @@
+use crate::expr::{self, ExprError};
+use crate::number::Number;
+use std::error::Error;
+use std::fmt;
+
+/// Why a line typed at the prompt isn't a usable guess. None of these
+/// use up an attempt.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GuessError {
+    /// Nothing (or only whitespace) was typed.
+    Empty,
+    /// The input isn't a number at all. `expected` says what a number
+    /// of the secret's type looks like (see `Number::EXAMPLE`).
+    NotANumber { input: String, expected: &'static str },
+    /// The input is a negative number, and the secret's type can't be
+    /// negative.
+    Negative(String),
+    /// The number is bigger than the secret's type can hold.
+    TooLarge { input: String, max: String },
+    /// The number is more negative than the secret's type can hold.
+    TooSmall { input: String, min: String },
+    /// The number is valid but outside the range the secret was drawn
+    /// from. The numbers are kept as text so the error works for every
+    /// Number type.
+    OutOfRange { guess: String, low: String, high: String },
+    /// The input is an arithmetic expression that can't be worked
+    /// out.
+    Expression { input: String, error: ExprError },
+}
+
+impl fmt::Display for GuessError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            GuessError::Empty => write!(f, "Please type a number before pressing enter."),
+            GuessError::NotANumber { input, expected } => {
+                write!(f, "'{input}' is not a number. Please type {expected}.")
+            }
+            GuessError::Negative(input) => {
+                write!(f, "'{input}' is negative. The secret number is never below zero.")
+            }
+            GuessError::TooLarge { input, max } => {
+                write!(f, "'{input}' is too large. Guesses can be at most {max}.")
+            }
+            GuessError::TooSmall { input, min } => {
+                write!(f, "'{input}' is too small. Guesses can be at least {min}.")
+            }
+            GuessError::OutOfRange { guess, low, high } => {
+                write!(f, "{guess} is out of range. The secret number is between {low} and {high}.")
+            }
+            GuessError::Expression { input, error } => {
+                write!(f, "Can't work out '{input}': {error}.")
+            }
+        }
+    }
+}
+
+impl Error for GuessError {}
+
+/// Turn a line typed at the prompt into a guess between `low` and
+/// `high` (inclusive). Surrounding whitespace, including the newline
+/// from read_line, is ignored. The guess has the same type as the
+/// bounds, which is u32 unless the game was started with --type.
+///
+/// Besides plain numbers, guesses can be arithmetic expressions such as
+/// `50+25`, `(1000/3)`, `0x40` or `1_000`, which are worked out first.
+pub fn parse_guess<T: Number>(input: &str, low: T, high: T) -> Result<T, GuessError> {
+    let guess = match T::parse(input) {
+        Err(GuessError::NotANumber { .. }) if expr::looks_like_expression(input) => {
+            let input = input.trim();
+            expr::evaluate(input).map_err(|error| GuessError::Expression {
+                input: input.to_string(),
+                error,
+            })?
+        }
+        result => result?,
+    };
+
+    if guess < low || guess > high {
+        return Err(GuessError::OutOfRange {
+            guess: guess.to_string(),
+            low: low.to_string(),
+            high: high.to_string(),
+        });
+    }
+
+    Ok(guess)
+}
+
+/// A minus sign followed by nothing but digits, such as "-5".
+pub(crate) fn is_negative_number(input: &str) -> bool {
+    match input.strip_prefix('-') {
+        Some(digits) => !digits.is_empty() && digits.chars().all(|c| c.is_ascii_digit()),
+        None => false,
+    }
+}
+
+/// Words the player can type at the prompt instead of a guess.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Command {
+    /// "quit" or "exit": stop playing.
+    Quit,
+    /// "save": save the game to pick it up later with
+    /// `guessing_game load`.
+    Save,
+}
+
+impl Command {
+    /// The command typed at the prompt, if the input is one. Case and
+    /// surrounding whitespace don't matter.
+    pub fn parse(input: &str) -> Option<Command> {
+        match input.trim().to_ascii_lowercase().as_str() {
+            "quit" | "exit" => Some(Command::Quit),
+            "save" => Some(Command::Save),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::expr::ExprErrorKind;
+
+    /// The message for `input` as a guess of type T between 1 and 100.
+    fn message<T: Number>(input: &str) -> String {
+        let (low, high) = (T::from_u32(1).unwrap(), T::from_u32(100).unwrap());
+        parse_guess::<T>(input, low, high).unwrap_err().to_string()
+    }
+
+    #[test]
+    fn guesses_are_trimmed() {
+        assert_eq!(parse_guess("  42\n", 1u32, 100), Ok(42));
+        assert_eq!(parse_guess("1", 1u32, 100), Ok(1));
+        assert_eq!(parse_guess("100", 1u32, 100), Ok(100));
+        assert_eq!(parse_guess("-3.5", -10.0f64, 10.0), Ok(-3.5));
+    }
+
+    #[test]
+    fn nothing_typed_is_empty() {
+        assert_eq!(parse_guess("\n", 1u32, 100), Err(GuessError::Empty));
+        assert_eq!(parse_guess("   ", 1.0f64, 100.0), Err(GuessError::Empty));
+        assert_eq!(message::<u32>(""), "Please type a number before pressing enter.");
+    }
+
+    #[test]
+    fn the_message_for_a_non_number_fits_the_type() {
+        assert_eq!(
+            parse_guess("abc", 1u32, 100),
+            Err(GuessError::NotANumber {
+                input: String::from("abc"),
+                expected: "a whole number, such as 42",
+            })
+        );
+        assert_eq!(message::<u32>("abc"), "'abc' is not a number. Please type a whole number, such as 42.");
+        assert_eq!(
+            message::<i64>("4 2"),
+            "'4 2' is not a number. Please type a whole number, such as 42 or -7."
+        );
+        assert_eq!(message::<f64>("abc"), "'abc' is not a number. Please type a number, such as 42 or 3.5.");
+        // Infinity and NaN parse as floats, but can't be guessed.
+        assert_eq!(message::<f32>("inf"), "'inf' is not a number. Please type a number, such as 42 or 3.5.");
+        assert!(matches!(parse_guess("NaN", 1.0f64, 100.0), Err(GuessError::NotANumber { .. })));
+    }
+
+    #[test]
+    fn negative_guesses_of_unsigned_types_are_explained() {
+        assert_eq!(parse_guess("-5", 1u32, 100), Err(GuessError::Negative(String::from("-5"))));
+        assert_eq!(message::<u8>("-5"), "'-5' is negative. The secret number is never below zero.");
+        // A signed type reads it, and only the range rules it out.
+        assert_eq!(message::<i32>("-5"), "-5 is out of range. The secret number is between 1 and 100.");
+    }
+
+    #[test]
+    fn numbers_the_type_cant_hold_are_too_large_or_too_small() {
+        assert_eq!(message::<u32>("4294967296"), "'4294967296' is too large. Guesses can be at most 4294967295.");
+        assert_eq!(message::<i8>("-129"), "'-129' is too small. Guesses can be at least -128.");
+        assert!(matches!(parse_guess("1e999", 1.0f64, 100.0), Err(GuessError::TooLarge { .. })));
+        assert!(matches!(parse_guess("-1e999", 1.0f64, 100.0), Err(GuessError::TooSmall { .. })));
+    }
+
+    #[test]
+    fn guesses_outside_the_range_are_out_of_range() {
+        assert_eq!(
+            parse_guess("101", 1u32, 100),
+            Err(GuessError::OutOfRange {
+                guess: String::from("101"),
+                low: String::from("1"),
+                high: String::from("100"),
+            })
+        );
+        assert_eq!(message::<u32>("0"), "0 is out of range. The secret number is between 1 and 100.");
+        assert_eq!(message::<u32>("60*2"), "120 is out of range. The secret number is between 1 and 100.");
+    }
+
+    #[test]
+    fn expressions_are_worked_out() {
+        assert_eq!(parse_guess("50+25", 1u32, 100), Ok(75));
+        assert_eq!(parse_guess(" (1000/3) / 10 ", 1u32, 100), Ok(33));
+        assert_eq!(parse_guess("0x40", 1u32, 100), Ok(64));
+
+        let err = parse_guess("10/(5-5)", 1u32, 100).unwrap_err();
+        let GuessError::Expression { input, error } = &err else {
+            panic!("{err:?}");
+        };
+        assert_eq!((input.as_str(), error.column, &error.kind), ("10/(5-5)", 3, &ExprErrorKind::DivisionByZero));
+        assert_eq!(err.to_string(), "Can't work out '10/(5-5)': column 3: division by zero.");
+    }
+
+    #[test]
+    fn commands_are_recognised() {
+        assert_eq!(Command::parse(" QUIT\n"), Some(Command::Quit));
+        assert_eq!(Command::parse("exit"), Some(Command::Quit));
+        assert_eq!(Command::parse("Save"), Some(Command::Save));
+        assert_eq!(Command::parse("50"), None);
+    }
+}
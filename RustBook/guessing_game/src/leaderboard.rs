@@ -0,0 +1,246 @@
+use crate::config::Difficulty;
+use serde::{Deserialize, Serialize};
+use std::fs::{self, File};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// One won game on the leaderboard.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Entry {
+    pub name: String,
+    pub low: u32,
+    pub high: u32,
+    /// The preset the range came from, or None for a custom range.
+    pub difficulty: Option<Difficulty>,
+    pub attempts: usize,
+    pub seconds: f64,
+    pub score: u64,
+    /// The day the game was played, as YYYY-MM-DD (UTC).
+    pub date: String,
+}
+
+/// Every recorded high score, stored as JSON.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Leaderboard {
+    pub entries: Vec<Entry>,
+}
+
+impl Leaderboard {
+    /// Read the leaderboard from `path`. A missing file is an empty
+    /// leaderboard rather than an error, since nobody has played yet.
+    pub fn load(path: &Path) -> io::Result<Leaderboard> {
+        match fs::read_to_string(path) {
+            Ok(contents) => Ok(serde_json::from_str(&contents)?),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(Leaderboard::default()),
+            Err(err) => Err(err),
+        }
+    }
+
+    /// Write the leaderboard to `path` atomically (see `write_json`).
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        write_json(path, self)
+    }
+
+    pub fn add(&mut self, entry: Entry) {
+        self.entries.push(entry);
+    }
+
+    /// The entries grouped by difficulty (easy through insane, then
+    /// custom ranges), each group sorted best score first. Ties go to
+    /// the fewest attempts, then the fastest time.
+    pub fn by_difficulty(&self) -> Vec<(Option<Difficulty>, Vec<&Entry>)> {
+        let groups = Difficulty::ALL
+            .into_iter()
+            .map(Some)
+            .chain([None]);
+
+        groups
+            .filter_map(|difficulty| {
+                let mut entries: Vec<&Entry> = self.entries
+                    .iter()
+                    .filter(|entry| entry.difficulty == difficulty)
+                    .collect();
+                if entries.is_empty() {
+                    return None;
+                }
+                entries.sort_by(|a, b| {
+                    b.score.cmp(&a.score)
+                        .then(a.attempts.cmp(&b.attempts))
+                        .then(a.seconds.total_cmp(&b.seconds))
+                });
+                Some((difficulty, entries))
+            })
+            .collect()
+    }
+
+    /// The leaderboard as a table, one section per difficulty.
+    pub fn render(&self) -> String {
+        let groups = self.by_difficulty();
+        if groups.is_empty() {
+            return String::from("The leaderboard is empty. Win a game to get on it!\n");
+        }
+
+        let mut out = String::new();
+        for (difficulty, entries) in groups {
+            match difficulty {
+                Some(difficulty) => {
+                    let (low, high) = difficulty.bounds();
+                    out.push_str(&format!("== {difficulty} ({low}-{high}) ==\n"));
+                }
+                None => out.push_str("== custom ==\n"),
+            }
+            out.push_str(&format!(
+                "{:>4}  {:<16} {:>7} {:>8} {:>9}  {:<10}  {}\n",
+                "#", "name", "score", "attempts", "seconds", "date", "range"
+            ));
+            for (rank, entry) in entries.iter().enumerate() {
+                out.push_str(&format!(
+                    "{:>4}  {:<16} {:>7} {:>8} {:>9.1}  {:<10}  {}-{}\n",
+                    rank + 1,
+                    entry.name,
+                    entry.score,
+                    entry.attempts,
+                    entry.seconds,
+                    entry.date,
+                    entry.low,
+                    entry.high
+                ));
+            }
+            out.push('\n');
+        }
+        out
+    }
+}
+
+/// Write `value` as JSON to `path` atomically: the JSON goes to a
+/// temporary file next to it, which is flushed to disk and then renamed
+/// over the old file. A crash part way through leaves either the old
+/// file or the new one, never half of each. Save files use this too.
+pub(crate) fn write_json(path: &Path, value: &impl Serialize) -> io::Result<()> {
+    if let Some(dir) = path.parent() {
+        if !dir.as_os_str().is_empty() {
+            fs::create_dir_all(dir)?;
+        }
+    }
+
+    let mut temp_path = path.as_os_str().to_owned();
+    temp_path.push(".tmp");
+    let temp_path = PathBuf::from(temp_path);
+
+    let mut file = File::create(&temp_path)?;
+    serde_json::to_writer_pretty(&mut file, value)?;
+    file.write_all(b"\n")?;
+    file.sync_all()?;
+    drop(file);
+
+    fs::rename(&temp_path, path)
+}
+
+/// Where the leaderboard lives unless --leaderboard-file says
+/// otherwise: guessing_game/leaderboard.json under the user's data
+/// directory (e.g. ~/.local/share on Linux), or the current directory if
+/// there isn't one.
+pub fn default_path() -> PathBuf {
+    dirs::data_dir()
+        .map(|dir| dir.join("guessing_game"))
+        .unwrap_or_default()
+        .join("leaderboard.json")
+}
+
+/// Today's date (UTC) as YYYY-MM-DD.
+pub fn today() -> String {
+    let seconds = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|since| since.as_secs())
+        .unwrap_or(0);
+    format_date(seconds)
+}
+
+/// Turn seconds since the Unix epoch into a YYYY-MM-DD date, using
+/// Howard Hinnant's days-to-civil algorithm so we don't need a whole
+/// date/time crate for one string.
+pub fn format_date(unix_seconds: u64) -> String {
+    let days = (unix_seconds / 86_400) as i64 + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days.rem_euclid(146_097);
+    let year_of_era = (day_of_era - day_of_era / 1_460 + day_of_era / 36_524
+        - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month = if month_index < 10 { month_index + 3 } else { month_index - 9 };
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+
+    format!("{year:04}-{month:02}-{day:02}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DAY: u64 = 86_400;
+
+    #[test]
+    fn the_epoch_is_the_first_of_january_1970() {
+        assert_eq!(format_date(0), "1970-01-01");
+        assert_eq!(format_date(DAY - 1), "1970-01-01");
+        assert_eq!(format_date(DAY), "1970-01-02");
+        assert_eq!(format_date(365 * DAY), "1971-01-01");
+    }
+
+    #[test]
+    fn leap_days_are_counted() {
+        // 2000 is a leap year because it is divisible by 400.
+        assert_eq!(format_date(951_782_400), "2000-02-29");
+        assert_eq!(format_date(951_782_400 + DAY), "2000-03-01");
+        assert_eq!(format_date(1_709_164_800), "2024-02-29");
+        assert_eq!(format_date(1_677_628_800 - DAY), "2023-02-28");
+        // 2100 isn't, because it is divisible by 100.
+        assert_eq!(format_date(4_107_456_000), "2100-02-28");
+        assert_eq!(format_date(4_107_456_000 + DAY), "2100-03-01");
+    }
+
+    #[test]
+    fn dates_past_2038_are_fine() {
+        assert_eq!(format_date(u64::from(u32::MAX)), "2106-02-07");
+        assert_eq!(format_date(253_402_300_799), "9999-12-31");
+    }
+
+    fn entry(name: &str, difficulty: Option<Difficulty>, score: u64) -> Entry {
+        Entry {
+            name: name.to_string(),
+            low: 1,
+            high: 100,
+            difficulty,
+            attempts: 5,
+            seconds: 12.5,
+            score,
+            date: String::from("2024-02-29"),
+        }
+    }
+
+    #[test]
+    fn a_saved_board_loads_the_same() {
+        let dir = std::env::temp_dir().join(format!("guessing_game-leaderboard-{}", std::process::id()));
+        let path = dir.join("scores").join("leaderboard.json");
+        assert_eq!(Leaderboard::load(&path).unwrap(), Leaderboard::default());
+
+        let mut board = Leaderboard::default();
+        board.add(entry("ann", Some(Difficulty::Easy), 700));
+        board.add(entry("bob", None, 900));
+        // write_json makes the missing directories.
+        board.save(&path).unwrap();
+        assert_eq!(Leaderboard::load(&path).unwrap(), board);
+        assert!(!dir.join("scores").join("leaderboard.json.tmp").exists());
+
+        board.add(entry("cy", Some(Difficulty::Easy), 800));
+        board.save(&path).unwrap();
+        let loaded = Leaderboard::load(&path).unwrap();
+        assert_eq!(loaded, board);
+        let easy: Vec<&str> = loaded.by_difficulty()[0].1.iter().map(|entry| entry.name.as_str()).collect();
+        assert_eq!(easy, ["cy", "ann"]);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,431 @@
+use crate::game::{range_size, Game, Host, Outcome};
+use crate::solver::Solver;
+use rand::Rng;
+use std::cmp::Ordering;
+
+/// How likely the host is to lie about a wrong guess while it still has
+/// lies left.
+pub const LIE_PROBABILITY: f64 = 0.25;
+
+/// One answer the host gave: what the truth was and what it said.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Answer {
+    pub guess: u32,
+    pub truth: Ordering,
+    pub reported: Ordering,
+}
+
+impl Answer {
+    pub fn is_lie(&self) -> bool {
+        self.truth != self.reported
+    }
+}
+
+/// A host for Ulam's game: the guessing game where "Too small!" and
+/// "Too big!" may be lies, up to `max_lies` times in a game.
+///
+/// "You win!" is never a lie, so a correct guess always ends the game.
+/// The host keeps every answer so the lies can be revealed at the end.
+/// It borrows the Game it hosts, so the game (with its secret and
+/// history) is still there for the summary afterwards.
+pub struct LiarHost<'a, R: Rng> {
+    game: &'a mut Game,
+    max_lies: usize,
+    answers: Vec<Answer>,
+    rng: R,
+}
+
+impl<'a, R: Rng> LiarHost<'a, R> {
+    /// Host `game`, lying at random (with LIE_PROBABILITY) until
+    /// `max_lies` lies have been told.
+    pub fn new(game: &'a mut Game, max_lies: usize, rng: R) -> LiarHost<'a, R> {
+        LiarHost {
+            game,
+            max_lies,
+            answers: Vec::new(),
+            rng,
+        }
+    }
+
+    /// Submit a guess to the hosted game. The returned Outcome's
+    /// ordering is what the host *says*, which may be a lie.
+    fn answer(&mut self, guess: u32) -> Outcome {
+        let mut outcome = self.game.submit_guess(guess);
+        let truth = outcome.ordering;
+
+        if truth != Ordering::Equal
+            && self.lies_told() < self.max_lies
+            && self.rng.gen_bool(LIE_PROBABILITY)
+        {
+            outcome.ordering = truth.reverse();
+        }
+
+        self.answers.push(Answer {
+            guess,
+            truth,
+            reported: outcome.ordering,
+        });
+        outcome
+    }
+
+    pub fn game(&self) -> &Game {
+        self.game
+    }
+
+    pub fn max_lies(&self) -> usize {
+        self.max_lies
+    }
+
+    pub fn lies_told(&self) -> usize {
+        self.answers.iter().filter(|answer| answer.is_lie()).count()
+    }
+
+    pub fn answers(&self) -> &[Answer] {
+        &self.answers
+    }
+}
+
+impl<R: Rng> Host for LiarHost<'_, R> {
+    fn low(&self) -> u32 {
+        self.game.low()
+    }
+
+    fn high(&self) -> u32 {
+        self.game.high()
+    }
+
+    fn remaining(&self) -> Option<usize> {
+        self.game.remaining()
+    }
+
+    fn is_over(&self) -> bool {
+        self.game.is_over()
+    }
+
+    fn submit_guess(&mut self, guess: u32) -> Outcome {
+        self.answer(guess)
+    }
+}
+
+/// The answers as a list, one per line, with the lies marked.
+pub fn render_answers(answers: &[Answer]) -> String {
+    let says = |ordering: Ordering| match ordering {
+        Ordering::Less => "Too small!",
+        Ordering::Greater => "Too big!",
+        Ordering::Equal => "You win!",
+    };
+
+    let mut out = String::new();
+    for (index, answer) in answers.iter().enumerate() {
+        let line = format!("#{:<3} {:>10}  {}", index + 1, answer.guess, says(answer.reported));
+        if answer.is_lie() {
+            out.push_str(&format!("{line:<28}  <- lie, it was {}\n", says(answer.truth)));
+        } else {
+            out.push_str(&format!("{line}\n"));
+        }
+    }
+    out
+}
+
+/// sum of C(questions, i) for i in 0..=lies: the number of ways to
+/// place at most `lies` lies among `questions` answers. This is a f64
+/// because it only needs to be compared and can get very large.
+fn volume(questions: u32, lies: usize) -> f64 {
+    let mut total = 0.0;
+    let mut binomial = 1.0;
+    for i in 0..=lies.min(questions as usize) {
+        if i > 0 {
+            binomial = binomial * (questions as usize - i + 1) as f64 / i as f64;
+        }
+        total += binomial;
+    }
+    total
+}
+
+/// Berlekamp's volume bound for Ulam's game: the smallest number of
+/// questions q with n * sum(C(q, i) for i <= k) <= 2^q. With yes/no
+/// questions, no strategy can always identify one of n numbers in
+/// fewer questions when up to k answers may be lies. With no lies this
+/// is ceil(log2(n)).
+pub fn volume_bound(size: u64, max_lies: usize) -> usize {
+    let mut questions = 0;
+    while size as f64 * volume(questions, max_lies) > 2f64.powi(questions as i32) {
+        questions += 1;
+    }
+    questions as usize
+}
+
+/// The attempts the UlamSolver is allowed: the volume bound for
+/// identifying the secret, plus the guess that names it (in this game
+/// the secret has to be guessed, not just worked out).
+pub fn default_max_attempts(low: u32, high: u32, max_lies: usize) -> usize {
+    volume_bound(range_size(low, high), max_lies) + 1
+}
+
+/// A run of neighbouring candidates that would all mean the same number
+/// of lies had been told.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Segment {
+    low: u32,
+    high: u32,
+    lies: usize,
+}
+
+/// A solver for Ulam's game, following Berlekamp's weighting strategy.
+///
+/// Every number that is still possible is kept along with how many of
+/// the answers so far would have to be lies if it were the secret;
+/// numbers needing more than `max_lies` lies are ruled out. Each number
+/// x is weighted by the ways the remaining lies could still be spread
+/// over the remaining questions, and each guess is the one that splits
+/// the total weight between "Too small!" and "Too big!" as evenly as
+/// possible, so whichever answer comes back (true or not) removes as
+/// much uncertainty as it can.
+#[derive(Clone)]
+pub struct UlamSolver {
+    max_lies: usize,
+    candidates: Vec<Segment>,
+}
+
+impl UlamSolver {
+    pub fn new(low: u32, high: u32, max_lies: usize) -> UlamSolver {
+        UlamSolver {
+            max_lies,
+            candidates: vec![Segment { low, high, lies: 0 }],
+        }
+    }
+
+    /// The numbers the secret could still be, with how many lies each
+    /// would mean.
+    pub fn candidates(&self) -> impl Iterator<Item = (u32, u32, usize)> + '_ {
+        self.candidates
+            .iter()
+            .map(|segment| (segment.low, segment.high, segment.lies))
+    }
+
+    fn weight_of(&self, count: u64, lies: usize, questions: u32) -> f64 {
+        if lies > self.max_lies {
+            return 0.0;
+        }
+        count as f64 * volume(questions, self.max_lies - lies)
+    }
+
+    /// The total weight with `questions` questions to go.
+    fn weight(&self, questions: u32) -> f64 {
+        self.candidates
+            .iter()
+            .map(|segment| {
+                let count = u64::from(segment.high - segment.low) + 1;
+                self.weight_of(count, segment.lies, questions)
+            })
+            .sum()
+    }
+
+    /// The weight left after guessing `guess` and hearing "Too small!"
+    /// (`Ordering::Less`), and after hearing "Too big!", with
+    /// `questions` questions to go afterwards.
+    fn split(&self, guess: u32, questions: u32) -> (f64, f64) {
+        let mut too_small = 0.0;
+        let mut too_big = 0.0;
+
+        for segment in &self.candidates {
+            // Numbers below the guess: "Too big!" is the truth for
+            // them, "Too small!" would be a lie.
+            if segment.low < guess {
+                let count = u64::from(segment.high.min(guess - 1) - segment.low) + 1;
+                too_big += self.weight_of(count, segment.lies, questions);
+                too_small += self.weight_of(count, segment.lies + 1, questions);
+            }
+            // Numbers above the guess: the other way round.
+            if segment.high > guess {
+                let count = u64::from(segment.high - segment.low.max(guess + 1)) + 1;
+                too_small += self.weight_of(count, segment.lies, questions);
+                too_big += self.weight_of(count, segment.lies + 1, questions);
+            }
+        }
+
+        (too_small, too_big)
+    }
+
+    /// The fewest questions q for which the current weight fits in 2^q.
+    fn questions_needed(&self) -> u32 {
+        let mut questions = 0;
+        while self.weight(questions) > 2f64.powi(questions as i32) {
+            questions += 1;
+        }
+        questions
+    }
+}
+
+impl Solver for UlamSolver {
+    fn name(&self) -> &str {
+        "ulam"
+    }
+
+    fn next_guess(&mut self) -> u32 {
+        let first = self.candidates[0].low;
+        let last = self.candidates[self.candidates.len() - 1].high;
+        if first == last {
+            return first;
+        }
+
+        // The "Too small!" weight only shrinks as the guess goes up and
+        // the "Too big!" weight only grows, so a binary search finds the
+        // guess where they cross.
+        let questions = self.questions_needed().saturating_sub(1);
+        let (mut low, mut high) = (first, last);
+        while low < high {
+            let mid = low + (high - low) / 2;
+            let (too_small, too_big) = self.split(mid, questions);
+            if too_small > too_big {
+                low = mid + 1;
+            } else {
+                high = mid;
+            }
+        }
+
+        // The crossing point or the number just before it, whichever
+        // leaves the smaller worst case.
+        let worst = |guess: u32| {
+            let (too_small, too_big) = self.split(guess, questions);
+            too_small.max(too_big)
+        };
+        if low > first && worst(low - 1) <= worst(low) {
+            low - 1
+        } else {
+            low
+        }
+    }
+
+    fn feedback(&mut self, guess: u32, ordering: Ordering) {
+        if ordering == Ordering::Equal {
+            self.candidates = vec![Segment { low: guess, high: guess, lies: 0 }];
+            return;
+        }
+
+        let mut next: Vec<Segment> = Vec::new();
+        for segment in &self.candidates {
+            // The guess itself is ruled out: "You win!" is never a lie.
+            let below = (segment.low < guess).then(|| (segment.low, segment.high.min(guess - 1)));
+            let above = (segment.high > guess).then(|| (segment.low.max(guess + 1), segment.high));
+
+            // Which side was the host's answer true for?
+            let (truthful, lied) = match ordering {
+                Ordering::Less => (above, below),
+                _ => (below, above),
+            };
+            for (range, lies) in [(lied, segment.lies + 1), (truthful, segment.lies)] {
+                if let Some((low, high)) = range {
+                    if lies <= self.max_lies {
+                        next.push(Segment { low, high, lies });
+                    }
+                }
+            }
+        }
+
+        // Keep the segments in order and merge neighbours that now need
+        // the same number of lies.
+        next.sort_by_key(|segment| segment.low);
+        let mut merged: Vec<Segment> = Vec::new();
+        for segment in next {
+            match merged.last_mut() {
+                Some(last) if last.lies == segment.lies && last.high.checked_add(1) == Some(segment.low) => {
+                    last.high = segment.high;
+                }
+                _ => merged.push(segment),
+            }
+        }
+        self.candidates = merged;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::seeded_rng;
+
+    #[test]
+    fn without_lies_the_bound_is_binary_search() {
+        assert_eq!(volume_bound(1, 0), 0);
+        assert_eq!(volume_bound(2, 0), 1);
+        assert_eq!(volume_bound(100, 0), 7);
+        assert_eq!(volume_bound(128, 0), 7);
+        assert_eq!(volume_bound(1 << 32, 0), 32);
+    }
+
+    #[test]
+    fn each_lie_costs_more_questions() {
+        // 100 * (1 + 11) = 1200 fits in 2^11 = 2048, but 100 * 11
+        // doesn't fit in 1024.
+        assert_eq!(volume_bound(100, 1), 11);
+        assert_eq!(default_max_attempts(1, 100, 1), 12);
+        // Pelc's answer to Ulam's original question: a number up to a
+        // million with one lie takes 25 questions.
+        assert_eq!(volume_bound(1_000_000, 1), 25);
+        assert!(volume_bound(100, 2) > volume_bound(100, 1));
+        for (size, lies) in [(100, 2), (1000, 3), (1 << 20, 2)] {
+            let q = volume_bound(size, lies) as u32;
+            assert!(size as f64 * volume(q, lies) <= 2f64.powi(q as i32));
+            assert!(size as f64 * volume(q - 1, lies) > 2f64.powi(q as i32 - 1));
+        }
+    }
+
+    /// Play the solver against a host who lies about exactly the
+    /// answers in `lies` (counting from 0), returning the guesses it
+    /// took to win.
+    fn solve(secret: u32, max_lies: usize, lies: &[usize]) -> usize {
+        let mut solver = UlamSolver::new(1, 100, max_lies);
+        for attempt in 0..100 {
+            let guess = solver.next_guess();
+            let truth = guess.cmp(&secret);
+            if truth == Ordering::Equal {
+                return attempt + 1;
+            }
+            let reported = if lies.contains(&attempt) { truth.reverse() } else { truth };
+            solver.feedback(guess, reported);
+            assert!(solver.candidates().any(|(low, high, _)| (low..=high).contains(&secret)));
+        }
+        panic!("{secret} was never found");
+    }
+
+    #[test]
+    fn the_solver_wins_within_the_default_attempts_whichever_answer_is_a_lie() {
+        let max_attempts = default_max_attempts(1, 100, 1);
+        for secret in 1..=100 {
+            assert!(solve(secret, 1, &[]) <= max_attempts, "{secret} with no lie");
+            for lie in 0..max_attempts {
+                assert!(solve(secret, 1, &[lie]) <= max_attempts, "{secret} with lie {lie}");
+            }
+        }
+    }
+
+    #[test]
+    fn the_solver_copes_with_two_lies() {
+        let max_attempts = default_max_attempts(1, 100, 2);
+        for secret in (1..=100).step_by(7) {
+            for first in 0..6 {
+                for second in first + 1..max_attempts {
+                    assert!(solve(secret, 2, &[first, second]) <= max_attempts, "{secret} {first} {second}");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn the_host_keeps_to_its_budget_and_never_lies_about_a_win() {
+        for seed in 0..20 {
+            let mut game = Game::with_secret(1, 100, 37);
+            let mut host = LiarHost::new(&mut game, 2, seeded_rng(Some(seed)));
+            for guess in (1..=100).rev() {
+                let outcome = host.submit_guess(guess);
+                if guess == 37 {
+                    assert!(outcome.is_win());
+                    break;
+                }
+            }
+            assert!(host.lies_told() <= 2);
+            assert!(host.answers().iter().all(|answer| answer.guess.cmp(&37) == answer.truth));
+            assert!(!host.answers().last().unwrap().is_lie());
+        }
+    }
+}
@@ -0,0 +1,39 @@
+// The guessing game library crate. The binary in main.rs is only a
+// thin front end that reads lines from the terminal and prints the
+// results; everything that decides how the game plays out lives here
+// so it can be reused (and tested) without touching stdin or stdout.
+
+// A package can contain both a src/main.rs and a src/lib.rs. Cargo
+// builds them as two crates with the same name as the package: a
+// binary crate and a library crate. The binary crate refers to the
+// library through its name, e.g. `use guessing_game::Game;`.
+
+pub mod code;
+pub mod config;
+pub mod ending;
+pub mod evil;
+pub mod expr;
+pub mod feedback;
+pub mod game;
+pub mod hotseat;
+pub mod http;
+pub mod input;
+pub mod leaderboard;
+pub mod liar;
+pub mod number;
+pub mod protocol;
+pub mod reverse;
+pub mod save;
+pub mod score;
+pub mod secret;
+pub mod server;
+pub mod solver;
+pub mod terminal;
+pub mod tournament;
+pub mod transcript;
+pub mod tui;
+pub mod word;
+
+pub use config::{Config, Difficulty, Mode};
+pub use ending::Ending;
+pub use game::{seeded_rng, Game, Host, Outcome};
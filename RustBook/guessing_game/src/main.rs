@@ -1,52 +1,1132 @@
-use std::io;
-use rand::Rng;
-use std::cmp::Ordering;
+use rand::rngs::StdRng;
+use std::env;
+use std::fs;
+use std::io::{self, IsTerminal};
+use std::net::TcpListener;
+use std::process;
+use std::thread;
+use std::time::Duration;
+use guessing_game::code::{parse_code, CodeGame, CodeRules, KnuthSolver};
+use guessing_game::config::{TypedRange, USAGE};
+use guessing_game::ending::USAGE_EXIT_CODE;
+use guessing_game::hotseat::HotSeat;
+use guessing_game::http::{self, ApiSettings};
+use guessing_game::input::{parse_guess, Command, GuessError};
+use guessing_game::evil::EvilHost;
+use guessing_game::feedback;
+use guessing_game::game::optimal_attempts;
+use guessing_game::leaderboard::{self, Entry, Leaderboard};
+use guessing_game::liar::{self, LiarHost, UlamSolver};
+use guessing_game::number::{Number, NumberVisitor};
+use guessing_game::protocol::{self, Setup};
+use guessing_game::reverse::{Answer, Guesser};
+use guessing_game::save::SavedGame;
+use guessing_game::score::game_score;
+use guessing_game::secret::{Distribution, SecretSource};
+use guessing_game::server::{self, RoomSettings};
+use guessing_game::solver::{self, BinarySearch, Solver, WeightedSearch};
+use guessing_game::terminal::{Input, Terminal};
+use guessing_game::tournament::{self, CommandStrategy, SolverStrategy, Strategy};
+use guessing_game::transcript::{Check, Transcript};
+use guessing_game::tui;
+use guessing_game::word::{self, parse_word, WordGame, WordList};
+use guessing_game::{seeded_rng, Config, Ending, Game, Host, Mode, Outcome};
 
 fn main() {
-    println!("Guess the number!");
+    // Read the range (or difficulty preset) from the command line. Bad
+    // arguments print the problem and exit rather than panicking.
+    let mut config = Config::build(env::args()).unwrap_or_else(|err| {
+        if err == USAGE {
+            println!("{USAGE}");
+            process::exit(0);
+        }
+        eprintln!("Problem parsing arguments: {err}");
+        process::exit(USAGE_EXIT_CODE);
+    });
+
+    // --leaderboard shows the high scores instead of playing a game.
+    if config.show_leaderboard {
+        match Leaderboard::load(&config.leaderboard_path) {
+            Ok(board) => print!("{}", board.render()),
+            Err(err) => {
+                eprintln!("Could not read {}: {err}", config.leaderboard_path.display());
+                process::exit(Ending::Error.exit_code());
+            }
+        }
+        return;
+    }
+
+    // Bulls and cows has a code instead of a number, so it has its own
+    // game loop.
+    if let Some(rules) = config.code {
+        process::exit(code_play(&config, rules).exit_code());
+    }
+
+    // So does the word game.
+    if config.word {
+        process::exit(word_play(&config).exit_code());
+    }
+
+    // In reverse mode the player holds the secret, so there is no Game
+    // to set up, and hot-seat games set up one Game per player.
+    if config.mode == Mode::Reverse {
+        let terminal = spawn_terminal();
+        process::exit(reverse_play(&config, &terminal).exit_code());
+    }
+    if config.mode == Mode::HotSeat {
+        let terminal = spawn_terminal();
+        process::exit(hotseat_play(&config, &terminal).exit_code());
+    }
+
+    // The server and client don't play a game in this process at all.
+    if config.mode == Mode::Serve {
+        process::exit(serve(&config).exit_code());
+    }
+    if config.mode == Mode::Http {
+        process::exit(serve_http(&config).exit_code());
+    }
+    if config.mode == Mode::Connect {
+        if let Err(err) = server::connect(config.address.as_str()) {
+            eprintln!("Could not play on {}: {err}", config.address);
+            process::exit(Ending::Error.exit_code());
+        }
+        process::exit(Ending::Quit.exit_code());
+    }
+
+    // A tournament plays thousands of games without anyone watching.
+    if config.mode == Mode::Tournament {
+        process::exit(tournament_play(&config).exit_code());
+    }
+
+    // A bot playing over --protocol gets JSON instead of the banner
+    // and prompts.
+    if config.protocol.is_some() {
+        process::exit(protocol_play(&config).exit_code());
+    }
+
+    // Games over another number type than u32 are generic, so they
+    // are played by a visitor that knows the type.
+    if let Some(typed) = &config.typed {
+        process::exit(typed.kind.visit(TypedPlay { config: &config, typed }).exit_code());
+    }
+
+    // `replay` plays back a transcript instead of a game.
+    if config.mode == Mode::Replay {
+        process::exit(replay(&config).exit_code());
+    }
+
+    // `load` carries on with a saved game, which brings its own range
+    // and limits, instead of drawing a new secret.
+    let resumed = if config.mode == Mode::Load {
+        let (game, rng) = load_game(&mut config);
+        Some((game, rng))
+    } else {
+        None
+    };
+
+    println!("Guess the number {}!", config.describe_range());
+    let source = secret_source(&config);
+    if config.secret != Distribution::Uniform {
+        println!("The secret is {}.", source.describe());
+    }
+
+    // Generate the secret number from the configured range (and
+    // distribution). With --seed the same seed always picks the same
+    // secret, so there is no need to print the secret number while
+    // testing. The Game keeps track of the secret, the bounds, and
+    // every guess we make.
+    let (mut game, mut rng) = match resumed {
+        Some((game, rng)) => {
+            print_resumed(&config, &game);
+            (game, rng)
+        }
+        None => {
+            let mut rng = seeded_rng(config.seed);
+            let game = Game::from_source(source.as_ref(), &mut rng)
+                .with_max_attempts(config.max_attempts);
+            (game, rng)
+        }
+    };
+    // Only a line-by-line game with a human playing by the usual rules
+    // can be saved.
+    let saving = matches!(config.mode, Mode::Human | Mode::Load)
+        && config.lies == 0
+        && !config.evil
+        && !(config.tui && io::stdin().is_terminal() && io::stdout().is_terminal());
+    let mut saved = false;
+
+    let ending = if config.lies > 0 {
+        liar_play(&config, &mut game, &mut rng)
+    } else if config.evil {
+        evil_play(&config, &mut game)
+    } else if config.mode == Mode::Auto {
+        // A solver that knows the distribution can make use of it.
+        let ending = if config.secret == Distribution::Uniform {
+            auto_play(&mut game, &mut BinarySearch::new(config.low, config.high))
+        } else {
+            auto_play(&mut game, &mut WeightedSearch::new(source.as_ref(), config.max_attempts))
+        };
+        print_binary_bound(&game);
+        ending
+    } else if config.tui && io::stdin().is_terminal() && io::stdout().is_terminal() {
+        let title = format!("Guess the number {}!", config.describe_range());
+        tui::play(&mut game, &title).unwrap_or_else(|err| {
+            eprintln!("The terminal UI failed: {err}");
+            Ending::Error
+        })
+    } else {
+        // Pipes can't drive a full-screen UI, so --tui quietly falls
+        // back to the line-by-line game.
+        let terminal = spawn_terminal();
+        if config.mode == Mode::Human && config.save_path.exists() {
+            println!(
+                "There is already a game saved in {}. Run `guessing_game load` to play it; this game only replaces it if you type \"save\".",
+                config.save_path.display()
+            );
+        }
+        println!("Type \"quit\" or \"exit\" (or press CTRL + C) to give up, or \"save\" to save the game.");
+
+        // Typing "save" always saves. Otherwise the save is kept up to
+        // date after every guess, so even closing the terminal doesn't
+        // lose the game, and written once more on quitting to record
+        // the time played, but only while the save file is this game's
+        // own: one this game didn't write or load was the player's to
+        // replace.
+        let mut save = |game: &Game, when: Save| {
+            let owned = saved || config.mode == Mode::Load || !config.save_path.exists();
+            match when {
+                Save::Asked => {}
+                Save::AfterGuess if !owned || game.is_over() => return,
+                Save::AfterGuess => {}
+                Save::Quitting if !owned => {
+                    println!(
+                        "The game saved in {} was left as it was, so this one isn't saved.",
+                        config.save_path.display()
+                    );
+                    return;
+                }
+                Save::Quitting => {}
+            }
+            match save_game(&config, game, &mut rng) {
+                Ok(()) => {
+                    saved = true;
+                    if !matches!(when, Save::AfterGuess) {
+                        println!(
+                            "Game saved to {}. Run `guessing_game load` to carry on.",
+                            config.save_path.display()
+                        );
+                    }
+                }
+                Err(err) => eprintln!("Could not save the game to {}: {err}", config.save_path.display()),
+            }
+        };
+        let ending = play(
+            &mut game,
+            &terminal,
+            |game, outcome| feedback::describe(config.feedback, game, outcome),
+            Some(&mut save),
+        );
+        if matches!(ending, Ending::Quit | Ending::Error) {
+            save(&game, Save::Quitting);
+        }
+        ending
+    };
+
+    // A game that is over can't be carried on, so its save goes. A
+    // save this game didn't write (or load) is left alone.
+    if saving && game.is_over() && (saved || config.mode == Mode::Load) {
+        if let Err(err) = fs::remove_file(&config.save_path) {
+            if err.kind() != io::ErrorKind::NotFound {
+                eprintln!("Could not remove the save {}: {err}", config.save_path.display());
+            }
+        }
+    }
+
+    // Every game ends with a summary, however it ended. A saved game
+    // keeps its secret.
+    match ending {
+        Ending::Won => {}
+        Ending::Lost => println!("You lose! The secret number was {}.", game.secret()),
+        Ending::Quit | Ending::Error if saved => {
+            println!("You {ending} after {} guesses.", game.attempts())
+        }
+        Ending::Quit | Ending::Error => println!(
+            "You {ending} after {} guesses. The secret number was {}.",
+            game.attempts(),
+            game.secret()
+        ),
+    }
+    println!(
+        "Score: {} ({} attempts, {:.1} seconds)",
+        game_score(&game),
+        game.attempts(),
+        game.elapsed().as_secs_f64()
+    );
+
+    // Wins go on the leaderboard (but not the computer's, and not
+    // liar or evil games, which play by other rules). A problem saving
+    // the score is reported but doesn't change how the game ended.
+    if ending == Ending::Won && matches!(config.mode, Mode::Human | Mode::Load) && config.lies == 0 && !config.evil {
+        if let Err(err) = record_win(&config, &game) {
+            eprintln!(
+                "Could not save the leaderboard to {}: {err}",
+                config.leaderboard_path.display()
+            );
+        }
+    }
+
+    // --record writes down how the game went. A saved game's secret
+    // stays out of the transcript.
+    if let Some(path) = &config.record_path {
+        let transcript = Transcript::record(&config, &game, ending, saved);
+        match transcript.save(path) {
+            Ok(()) => println!("Transcript written to {}.", path.display()),
+            Err(err) => eprintln!("Could not write the transcript to {}: {err}", path.display()),
+        }
+    }
+
+    // Win, loss, quit and error each exit with their own code.
+    process::exit(ending.exit_code());
+}
 
-    // Generate the secret number (between 1 and 100).
-    let secret_number = rand::thread_rng().gen_range(1..=100);
+/// The longest a replay waits between two guesses.
+const MAX_REPLAY_PAUSE: Duration = Duration::from_secs(10);
 
-    // Loop the game until the user enters the correct guess OR quits
-    // the program (with CTRL + C).
+/// The source of secrets picked with --secret. Config::build has
+/// already checked it can be made, but a problem is still reported
+/// rather than panicking.
+fn secret_source(config: &Config) -> Box<dyn SecretSource + Send> {
+    config.secret.source(config.low, config.high).unwrap_or_else(|err| {
+        eprintln!("Could not draw a secret: {err}");
+        process::exit(Ending::Error.exit_code());
+    })
+}
+
+/// Read the save for `load` and make the Config match it. Problems
+/// reading it end the program.
+fn load_game(config: &mut Config) -> (Game, StdRng) {
+    let path = config.save_path.clone();
+    let restored = SavedGame::load(&path)
+        .map_err(|err| match err.kind() {
+            io::ErrorKind::NotFound => format!("There is no saved game in {}.", path.display()),
+            _ => format!("Could not read the saved game {}: {err}", path.display()),
+        })
+        .and_then(|saved| {
+            let (game, rng) = saved
+                .restore()
+                .map_err(|err| format!("Could not load the saved game {}: {err}.", path.display()))?;
+            Ok((saved, game, rng))
+        });
+    let (saved, game, rng) = restored.unwrap_or_else(|err| {
+        eprintln!("{err}");
+        process::exit(Ending::Error.exit_code());
+    });
+
+    config.low = saved.low;
+    config.high = saved.high;
+    config.difficulty = saved.difficulty;
+    config.max_attempts = saved.max_attempts;
+    config.feedback = saved.feedback;
+    // The secret came from the save, not from any seed given now.
+    config.seed = None;
+    (game, rng)
+}
+
+/// Remind the player of a resumed game's guesses so far, answered the
+/// way they were the first time.
+fn print_resumed(config: &Config, game: &Game) {
+    println!("Welcome back! {} guesses so far:", game.attempts());
+    let mut replay = Game::with_secret(game.low(), game.high(), game.secret());
+    for &guess in game.history() {
+        let outcome = replay.submit_guess(guess);
+        println!("  {guess}: {}", feedback::describe(config.feedback, &replay, &outcome));
+    }
+}
+
+/// Save the game and the state of its random number generator.
+fn save_game(config: &Config, game: &Game, rng: &mut StdRng) -> io::Result<()> {
+    SavedGame::capture(game, config, rng).save(&config.save_path)
+}
+
+/// Lines from standard input and Ctrl-C both arrive through the
+/// terminal, so the game loops can end the game cleanly for either.
+fn spawn_terminal() -> Terminal {
+    Terminal::spawn().unwrap_or_else(|err| {
+        eprintln!("Could not set up the terminal: {err}");
+        process::exit(Ending::Error.exit_code());
+    })
+}
+
+/// Wait for the next line typed at the prompt. EOF, CTRL + C and the
+/// quit/exit commands come back as Err(Ending::Quit), and a failure
+/// reading standard input as Err(Ending::Error).
+fn next_line(terminal: &Terminal) -> Result<String, Ending> {
+    match terminal.next() {
+        Input::Line(line) => match Command::parse(&line) {
+            Some(Command::Quit) => Err(Ending::Quit),
+            Some(Command::Save) | None => Ok(line),
+        },
+        Input::Eof | Input::Interrupted => {
+            println!();
+            Err(Ending::Quit)
+        }
+        Input::Error(err) => {
+            eprintln!("Failed to read line: {err}");
+            Err(Ending::Error)
+        }
+    }
+}
+
+/// Why the game is being saved.
+enum Save {
+    /// The player typed "save".
+    Asked,
+    /// A guess was just made.
+    AfterGuess,
+    /// The game is ending without being won or lost.
+    Quitting,
+}
+
+/// Saves the game `play` is playing.
+type SaveFn<'a, H> = &'a mut dyn FnMut(&H, Save);
+
+/// Play until the player wins, runs out of attempts, quits (with
+/// quit/exit, CTRL + C or by closing standard input), or reading the
+/// input fails. `describe` turns each outcome into the line to print,
+/// and `save`, for games that can be saved, is called when the player
+/// types "save" and after every guess.
+fn play<T: Number, H: Host<T>>(
+    host: &mut H,
+    terminal: &Terminal,
+    describe: impl Fn(&H, &Outcome<T>) -> String,
+    mut save: Option<SaveFn<H>>,
+) -> Ending {
     loop {
-        println!("Please input your guess.");
-    
-        // We create a mutable (mut) string variable to store our guesses.
-        let mut guess = String::new();
-    
-        // This let's us read in user input and assign it to the guess 
-        // variable.
-        io::stdin()
-            .read_line(&mut guess)
-            .expect("Failed to read line");
-    
-        // Convert guess from string to uint32 with shadowing.
-        let guess: u32 = match guess
-            .trim()
-            .parse() {
-                // Ignore non-number guesses and repeat the loop for
-                // invalid input as part of error handling.
-                Ok(num) => num,
-                Err(_) => continue,
+        match host.remaining() {
+            Some(remaining) => println!("Please input your guess ({remaining} attempts left)."),
+            None => println!("Please input your guess."),
+        }
+
+        // Wait for the player to type a line. Rather than panicking
+        // with expect("Failed to read line"), EOF, CTRL + C and read
+        // errors all end the game, as does typing quit or exit.
+        let guess = match next_line(terminal) {
+            Ok(line) => line,
+            Err(ending) => return ending,
+        };
+        if Command::parse(&guess) == Some(Command::Save) {
+            match save.as_mut() {
+                Some(save) => save(host, Save::Asked),
+                None => println!("This game can't be saved."),
+            }
+            continue;
+        }
+
+        // Convert guess from string to the secret's type (uint32
+        // unless --type says otherwise) with shadowing, working it out
+        // first if it's an expression like 50+25. Invalid input (empty,
+        // not a number, negative, too large, outside the range, or a
+        // broken expression) gets a message saying what was wrong, and
+        // we ask again without using up an attempt.
+        let line = guess;
+        let guess: T = match parse_guess(&line, host.low(), host.high()) {
+            Ok(num) => num,
+            Err(err) => {
+                print_guess_error(&err);
+                continue;
+            }
+        };
+
+        // This line prints the string that now contains the user’s input
+        // (and what it came to, if it was an expression).
+        if line.trim() == guess.to_string() {
+            println!("You guessed: {}", guess);
+        } else {
+            println!("You guessed: {} = {guess}", line.trim());
+        }
+
+        // Compare the secret number to the guess. The game hands back
+        // an Outcome wrapping the Ordering from guess.cmp(&secret), and
+        // we print the matching message (too small, too big, or a
+        // win), or how hot or cold the guess is in hot/cold mode.
+        let outcome = host.submit_guess(guess);
+        println!("{}", describe(host, &outcome));
+        if let Some(save) = save.as_mut() {
+            save(host, Save::AfterGuess);
+        }
+
+        if outcome.is_win() {
+            return Ending::Won;
+        }
+        // Running out of attempts loses the game.
+        if outcome.is_loss() {
+            return Ending::Lost;
+        }
+    }
+}
+
+/// Plays a game over the Number type picked with --type.
+struct TypedPlay<'a> {
+    config: &'a Config,
+    typed: &'a TypedRange,
+}
+
+impl NumberVisitor for TypedPlay<'_> {
+    type Output = Ending;
+
+    fn visit<T: Number>(self) -> Ending {
+        typed_play::<T>(self.config, self.typed)
+    }
+}
+
+/// The number game over any Number type: the same loop as the u32
+/// game, but without the leaderboard, whose scores are for u32 games.
+/// Float games are won by a guess within the tolerance.
+fn typed_play<T: Number>(config: &Config, typed: &TypedRange) -> Ending {
+    // Config::build has already checked the bounds.
+    let bounds = match typed.bounds::<T>() {
+        Ok(bounds) => bounds,
+        Err(err) => {
+            eprintln!("Problem parsing arguments: {err}");
+            return Ending::Error;
+        }
+    };
+
+    println!("Guess the {} between {} and {}!", T::NAME, bounds.low, bounds.high);
+    if T::IS_FLOAT {
+        println!("Any guess within {} of the secret wins.", bounds.tolerance);
+    }
+
+    let mut rng = seeded_rng(config.seed);
+    let mut game = Game::with_rng(bounds.low, bounds.high, &mut rng)
+        .with_tolerance(bounds.tolerance)
+        .with_max_attempts(config.max_attempts);
+
+    let ending = if config.mode == Mode::Auto {
+        let ending = auto_play(&mut game, &mut BinarySearch::new(bounds.low, bounds.high));
+        println!(
+            "Took {} attempts. Binary search never needs more than {} here.",
+            game.attempts(),
+            T::attempts_needed(bounds.low, bounds.high, bounds.tolerance)
+        );
+        ending
+    } else {
+        let terminal = spawn_terminal();
+        println!("Type \"quit\" or \"exit\" (or press CTRL + C) to give up.");
+        play(&mut game, &terminal, |_, outcome| outcome.message().to_string(), None)
+    };
+
+    match ending {
+        Ending::Won => println!("Found it in {} guesses.", game.attempts()),
+        Ending::Lost => println!("You lose! The secret number was {}.", game.secret()),
+        Ending::Quit | Ending::Error => println!(
+            "You {ending} after {} guesses. The secret number was {}.",
+            game.attempts(),
+            game.secret()
+        ),
+    }
+    ending
+}
+
+/// Explain why a guess was rejected. Broken expressions also get the
+/// input back with a caret under the problem.
+fn print_guess_error(err: &GuessError) {
+    if let GuessError::Expression { input, error } = err {
+        println!("{}", error.pointer(input));
+    }
+    println!("{err}");
+}
+
+/// Bulls and cows: guess a secret code, and each guess is answered
+/// with how many digits are right and in the right place (bulls) and
+/// how many are right but in the wrong place (cows). With --auto,
+/// Knuth's strategy plays.
+fn code_play(config: &Config, rules: CodeRules) -> Ending {
+    let mut rng = seeded_rng(config.seed);
+    let mut game = CodeGame::new(rules, &mut rng).with_max_attempts(config.max_attempts);
+
+    let kind = if rules.repeats { "digits may repeat" } else { "all different" };
+    println!("Guess the {}-digit code ({kind})!", rules.length);
+    println!("A bull is a right digit in the right place, a cow a right digit in the wrong place.");
+
+    let ending = if config.mode == Mode::Auto {
+        let mut solver = KnuthSolver::new(rules);
+        println!("The computer is playing with Knuth's strategy.");
+        loop {
+            let guess = solver.next_guess();
+            let outcome = game.submit_guess(guess.clone());
+            solver.feedback(&guess, outcome.score);
+            println!(
+                "Guess #{}: {guess} -> {} ({} still possible)",
+                outcome.attempts,
+                outcome.score,
+                solver.candidates().len()
+            );
+            if outcome.is_win() {
+                break Ending::Won;
+            }
+            if outcome.is_loss() {
+                break Ending::Lost;
+            }
+        }
+    } else {
+        let terminal = spawn_terminal();
+        println!("Type \"quit\" or \"exit\" (or press CTRL + C) to give up.");
+        loop {
+            match game.remaining() {
+                Some(remaining) => println!("Please input your guess ({remaining} attempts left)."),
+                None => println!("Please input your guess."),
+            }
+
+            let line = match next_line(&terminal) {
+                Ok(line) => line,
+                Err(ending) => break ending,
             };
-    
-        // This line prints the string that now contains the user’s input. 
-        println!("You guessed: {}", guess);
-    
-        // Compare the secret number to the guess. Output a string to 
-        // terminal based on whether the value is too large, too small, or
-        // exactly the same.
-        match guess.cmp(&secret_number) {
-            Ordering::Less => println!("Too small!"),
-            Ordering::Greater => println!("Too big!"),
-            Ordering::Equal => {
-                // Let’s program the game to quit when the user wins by
-                // adding a break statement:
+            // Codes of the wrong length, with other characters or with
+            // repeated digits don't use up an attempt.
+            let guess = match parse_code(&line, rules) {
+                Ok(guess) => guess,
+                Err(err) => {
+                    println!("{err}");
+                    continue;
+                }
+            };
+
+            println!("You guessed: {guess}");
+            let outcome = game.submit_guess(guess);
+            if outcome.is_win() {
                 println!("You win!");
-                break;
-            },
+                break Ending::Won;
+            }
+            println!("{}", outcome.score);
+            if outcome.is_loss() {
+                break Ending::Lost;
+            }
         }
+    };
+
+    match ending {
+        Ending::Won => println!("Found the code in {} guesses.", game.attempts()),
+        Ending::Lost => println!("You lose! The code was {}.", game.secret()),
+        Ending::Quit | Ending::Error => println!(
+            "You {ending} after {} guesses. The code was {}.",
+            game.attempts(),
+            game.secret()
+        ),
+    }
+    ending
+}
+
+/// The word game: guess the secret word, and every letter of each
+/// guess comes back green (right letter, right place), yellow (in the
+/// word, but somewhere else) or gray (not in the word). It follows the
+/// same steps as the number game: read a line, trim and validate it,
+/// then compare it with the secret.
+fn word_play(config: &Config) -> Ending {
+    let words = match &config.word_list {
+        Some(path) => match WordList::load(path) {
+            Ok(words) => words,
+            Err(err) => {
+                eprintln!("Could not read the word list {}: {err}", path.display());
+                return Ending::Error;
+            }
+        },
+        None => WordList::bundled(),
+    };
+
+    let mut rng = seeded_rng(config.seed);
+    let mut game = WordGame::new(&words, &mut rng).with_max_attempts(config.max_attempts);
+    let terminal = spawn_terminal();
+
+    println!("Guess the {}-letter word!", words.length());
+    println!("🟩 right letter, right place  🟨 in the word, somewhere else  ⬜ not in the word");
+    println!("Type \"quit\" or \"exit\" (or press CTRL + C) to give up.");
+
+    let ending = loop {
+        match game.remaining() {
+            Some(remaining) => println!("Please input your guess ({remaining} attempts left)."),
+            None => println!("Please input your guess."),
+        }
+
+        let line = match next_line(&terminal) {
+            Ok(line) => line,
+            Err(ending) => break ending,
+        };
+
+        // Words of the wrong length, with other characters, or that
+        // aren't in the list get a message and don't use up an
+        // attempt.
+        let guess = match parse_word(&line, &words) {
+            Ok(guess) => guess,
+            Err(err) => {
+                println!("{err}");
+                continue;
+            }
+        };
+
+        let outcome = game.submit_guess(guess);
+        println!("{}", word::render(&outcome.guess, &outcome.marks));
+        if outcome.is_win() {
+            println!("You win!");
+            break Ending::Won;
+        }
+        if outcome.is_loss() {
+            break Ending::Lost;
+        }
+    };
+
+    match ending {
+        Ending::Won => println!("Found the word in {} guesses.", game.attempts()),
+        Ending::Lost => println!("You lose! The word was {}.", game.secret()),
+        Ending::Quit | Ending::Error => println!(
+            "You {ending} after {} guesses. The word was {}.",
+            game.attempts(),
+            game.secret()
+        ),
+    }
+    ending
+}
+
+/// Hot-seat mode: 2-8 players take turns at the same terminal. The
+/// prompt says whose turn it is, and the game ends with a table of
+/// standings. It counts as a win if anybody found their secret.
+fn hotseat_play(config: &Config, terminal: &Terminal) -> Ending {
+    let source = secret_source(config);
+    let mut rng = seeded_rng(config.seed);
+    let mut seat = if config.separate_secrets {
+        HotSeat::separate(&config.players, source.as_ref(), config.max_attempts, &mut rng)
+    } else {
+        HotSeat::shared(&config.players, source.as_ref(), config.max_attempts, &mut rng)
+    };
+
+    println!("Guess the number {}!", config.describe_range());
+    if config.secret != Distribution::Uniform {
+        println!("The secret is {}.", source.describe());
+    }
+    if seat.is_shared_secret() {
+        println!("{} players race to find the same secret number.", seat.players().len());
+    } else {
+        println!("{} players each get their own secret. Fewest guesses wins!", seat.players().len());
     }
-}
\ No newline at end of file
+    println!("Type \"quit\" or \"exit\" (or press CTRL + C) to stop the game.");
+
+    let ending = loop {
+        let player = seat.current();
+        let name = player.name.clone();
+        match player.game.remaining() {
+            Some(remaining) => println!(
+                "[{name}'s turn, guess #{}] Please input your guess ({remaining} attempts left).",
+                player.game.attempts() + 1
+            ),
+            None => println!(
+                "[{name}'s turn, guess #{}] Please input your guess.",
+                player.game.attempts() + 1
+            ),
+        }
+
+        let guess = match next_line(terminal) {
+            Ok(line) => line,
+            Err(ending) => break ending,
+        };
+
+        // Invalid input doesn't use up the player's attempt or their
+        // turn.
+        let guess = match parse_guess(&guess, config.low, config.high) {
+            Ok(num) => num,
+            Err(err) => {
+                print_guess_error(&err);
+                continue;
+            }
+        };
+
+        let index = seat.current_index();
+        let outcome = seat.submit_guess(guess);
+        let message = feedback::describe(config.feedback, &seat.players()[index].game, &outcome);
+        println!("{name} guessed {guess}: {message}");
+        if outcome.is_loss() {
+            println!("{name} is out of attempts!");
+        }
+
+        if seat.is_over() {
+            let anyone_won = seat.players().iter().any(|player| player.game.is_won());
+            break if anyone_won { Ending::Won } else { Ending::Lost };
+        }
+    };
+
+    if seat.is_shared_secret() {
+        println!("The secret number was {}.", seat.players()[0].game.secret());
+    } else {
+        for player in seat.players() {
+            println!("{}'s secret number was {}.", player.name, player.game.secret());
+        }
+    }
+    println!();
+    print!("{}", seat.render_standings());
+
+    ending
+}
+
+/// Let the computer play with the given solver, printing each guess
+/// and the answer it got.
+fn auto_play<T: Number>(host: &mut dyn Host<T>, solver: &mut dyn Solver<T>) -> Ending {
+    println!("The computer is playing with the {} strategy.", solver.name());
+
+    let last = solver::auto_play(host, solver, |outcome| {
+        println!(
+            "Guess #{}: {} -> {:?} ({})",
+            outcome.attempts,
+            outcome.guess,
+            outcome.ordering,
+            outcome.message()
+        );
+    });
+
+    match last {
+        Some(outcome) if outcome.is_win() => Ending::Won,
+        _ => Ending::Lost,
+    }
+}
+
+/// After an auto game, compare the number of attempts with the best
+/// worst case any strategy can guarantee.
+fn print_binary_bound(game: &Game) {
+    let bound = optimal_attempts(game.range_size());
+    println!(
+        "Took {} attempts. No strategy can guarantee fewer than ceil(log2({} + 1)) = {bound} for {} numbers.",
+        game.attempts(),
+        game.range_size(),
+        game.range_size()
+    );
+}
+
+/// Play against the evil host, which keeps the larger half of the
+/// numbers alive after every guess. Binary search still wins, but only
+/// with its very last guess.
+fn evil_play(config: &Config, game: &mut Game) -> Ending {
+    let mut host = EvilHost::new(game);
+    println!("I haven't picked my number yet, and I'll make you work for it.");
+
+    let ending = if config.mode == Mode::Auto {
+        let mut solver = BinarySearch::new(config.low, config.high);
+        let ending = auto_play(&mut host, &mut solver);
+        print_binary_bound(host.game());
+        ending
+    } else {
+        let terminal = spawn_terminal();
+        println!("Type \"quit\" or \"exit\" (or press CTRL + C) to give up.");
+        play(&mut host, &terminal, |_, outcome| outcome.message().to_string(), None)
+    };
+
+    if ending == Ending::Won {
+        println!("You pinned me down: {} was the only number left.", host.game().secret());
+    }
+    ending
+}
+
+/// Ulam's game: the host may lie about "Too small!" and "Too big!" up
+/// to `config.lies` times. A human or the lie-tolerant solver plays,
+/// and at the end every answer is listed with the lies marked.
+fn liar_play(config: &Config, game: &mut Game, rng: &mut impl rand::Rng) -> Ending {
+    let mut host = LiarHost::new(game, config.lies, rng);
+    println!(
+        "Careful: up to {} of my answers may be lies! (\"You win!\" never is.)",
+        config.lies
+    );
+
+    let ending = if config.mode == Mode::Auto {
+        let mut solver = UlamSolver::new(config.low, config.high, config.lies);
+        let ending = auto_play(&mut host, &mut solver);
+        let size = host.game().range_size();
+        println!(
+            "Took {} attempts. With up to {} lies, Berlekamp's volume bound is {} questions to pin down one of {size} numbers.",
+            host.game().attempts(),
+            config.lies,
+            liar::volume_bound(size, config.lies)
+        );
+        ending
+    } else {
+        let terminal = spawn_terminal();
+        println!("Type \"quit\" or \"exit\" (or press CTRL + C) to give up.");
+        play(&mut host, &terminal, |_, outcome| outcome.message().to_string(), None)
+    };
+
+    println!();
+    println!("I told {} of {} allowed lies:", host.lies_told(), host.max_lies());
+    print!("{}", liar::render_answers(host.answers()));
+    println!();
+
+    ending
+}
+
+/// Let a bot play over JSON lines on standard input and output (see
+/// protocol::play). Problems go to standard error, so standard output
+/// only ever has the protocol's messages on it.
+fn protocol_play(config: &Config) -> Ending {
+    let source = secret_source(config);
+    let mut rng = seeded_rng(config.seed);
+    let mut game = Game::from_source(source.as_ref(), &mut rng).with_max_attempts(config.max_attempts);
+    let setup = Setup {
+        difficulty: config.difficulty,
+        distribution: config.secret.name(),
+    };
+
+    match protocol::play(&mut game, &setup, io::stdin().lock(), io::stdout().lock(), None) {
+        Ok(ending) => ending,
+        Err(err) => {
+            eprintln!("Could not write to standard output: {err}");
+            Ending::Error
+        }
+    }
+}
+
+/// Run a tournament between the strategies picked with --strategies
+/// and --strategy-command, and print how each did.
+fn tournament_play(config: &Config) -> Ending {
+    let mut strategies: Vec<Box<dyn Strategy>> = Vec::new();
+    for name in &config.strategies {
+        let strategy = SolverStrategy::builtin(name).expect("Config::build only accepts built-in strategies");
+        strategies.push(Box::new(strategy));
+    }
+    for command in &config.strategy_commands {
+        let setup = Setup {
+            difficulty: config.difficulty,
+            distribution: config.secret.name(),
+        };
+        match CommandStrategy::start(command, setup) {
+            Ok(strategy) => strategies.push(Box::new(strategy)),
+            Err(err) => {
+                eprintln!("Could not run the tournament: {err}.");
+                return Ending::Error;
+            }
+        }
+    }
+
+    let settings = tournament::Settings {
+        low: config.low,
+        high: config.high,
+        max_attempts: config.max_attempts,
+        secret: config.secret.clone(),
+        games: config.games,
+        seed: config.seed,
+    };
+    println!(
+        "{} games for each of {} {}, with secrets {}.",
+        settings.games,
+        strategies.len(),
+        if strategies.len() == 1 { "strategy" } else { "strategies" },
+        config.describe_range()
+    );
+    if let Some(max_attempts) = settings.max_attempts {
+        println!("A game is lost after {max_attempts} guesses.");
+    }
+    if config.secret != Distribution::Uniform {
+        println!("The secret is {}.", secret_source(config).describe());
+    }
+    if let Some(seed) = config.seed {
+        println!("Seed {seed}: run with the same seed to get the same secrets.");
+    }
+    println!();
+
+    match tournament::run(&mut strategies, &settings) {
+        Ok(results) => {
+            print!("{}", tournament::render(&results));
+            Ending::Won
+        }
+        Err(err) => {
+            eprintln!("The tournament stopped: {err}.");
+            Ending::Error
+        }
+    }
+}
+
+/// Play back a transcript written with --record, pausing between
+/// guesses as long as the player did (divided by --speed), then check
+/// the answers against the seed the game was played with. A transcript
+/// that doesn't match its seed is an error.
+fn replay(config: &Config) -> Ending {
+    let path = &config.replay_path;
+    let transcript = match Transcript::load(path) {
+        Ok(transcript) => transcript,
+        Err(err) => {
+            eprintln!("Could not read the transcript {}: {err}", path.display());
+            return Ending::Error;
+        }
+    };
+
+    let who = if transcript.auto { "The computer" } else { transcript.player.as_str() };
+    println!(
+        "Replaying a game from {}. {who} had to guess a number between {} and {}.",
+        transcript.date, transcript.low, transcript.high
+    );
+    let mut last = 0.0;
+    for (index, step) in transcript.guesses.iter().enumerate() {
+        if config.replay_speed > 0.0 {
+            // A long think (or a damaged transcript) doesn't hold up the
+            // replay for more than MAX_REPLAY_PAUSE.
+            let pause = (step.seconds - last).max(0.0) / config.replay_speed;
+            let pause = Duration::try_from_secs_f64(pause).map_or(MAX_REPLAY_PAUSE, |pause| pause.min(MAX_REPLAY_PAUSE));
+            thread::sleep(pause);
+        }
+        last = step.seconds;
+        println!("Guess #{}: {} -> {}", index + 1, step.guess, step.feedback);
+    }
+    match transcript.secret {
+        Some(secret) => println!(
+            "{} {} after {} guesses. The secret number was {secret}.",
+            who,
+            transcript.ending,
+            transcript.guesses.len()
+        ),
+        None => println!(
+            "{} {} after {} guesses, and saved the game.",
+            who,
+            transcript.ending,
+            transcript.guesses.len()
+        ),
+    }
+
+    match transcript.verify() {
+        Ok(Check::Seed(seed)) => {
+            println!("Verified: seed {seed} gives every answer in the transcript.");
+            Ending::Won
+        }
+        Ok(Check::Secret) => {
+            println!("The game wasn't seeded, but every answer fits the recorded secret.");
+            Ending::Won
+        }
+        Ok(Check::Unchecked) => {
+            println!("The game wasn't seeded, so the answers can't be checked.");
+            Ending::Won
+        }
+        Err(err) => {
+            eprintln!("The transcript doesn't match: {err}.");
+            Ending::Error
+        }
+    }
+}
+
+/// Reverse mode: the player thinks of a number and the program guesses
+/// it with binary search. The player answers "higher", "lower" or
+/// "correct"; answers that contradict each other are called out as
+/// cheating instead of looping forever. The program "wins" when it
+/// finds the number and the player "loses" if they cheat.
+fn reverse_play(config: &Config, terminal: &Terminal) -> Ending {
+    println!("Think of a number {}, and I'll guess it!", config.describe_range());
+    println!("Answer each guess with \"higher\", \"lower\" or \"correct\" (or \"quit\").");
+
+    let mut guesser = Guesser::new(config.low, config.high);
+
+    loop {
+        let guess = guesser.next_guess();
+
+        // Keep asking until we get an answer we understand.
+        let answer = loop {
+            println!("Is it {guess}?");
+
+            let line = match next_line(terminal) {
+                Ok(line) => line,
+                Err(ending) => {
+                    println!("You {ending} after {} guesses.", guesser.attempts());
+                    return ending;
+                }
+            };
+            match Answer::parse(&line) {
+                Some(answer) => break answer,
+                None => println!("Please answer \"higher\", \"lower\" or \"correct\"."),
+            }
+        };
+
+        if let Err(inconsistency) = guesser.answer(guess, answer) {
+            println!("Cheater! Your answers don't add up: {inconsistency}.");
+            return Ending::Lost;
+        }
+
+        if answer == Answer::Correct {
+            println!("I got it in {} guesses!", guesser.attempts());
+            return Ending::Won;
+        }
+    }
+}
+
+/// Host multiplayer rooms until the server fails (or is stopped with
+/// CTRL + C).
+fn serve(config: &Config) -> Ending {
+    let listener = match TcpListener::bind(config.address.as_str()) {
+        Ok(listener) => listener,
+        Err(err) => {
+            eprintln!("Could not listen on {}: {err}", config.address);
+            return Ending::Error;
+        }
+    };
+    println!(
+        "Serving guessing game rooms {} on {}.",
+        config.describe_range(),
+        config.address
+    );
+
+    let settings = RoomSettings {
+        low: config.low,
+        high: config.high,
+        max_attempts: config.max_attempts,
+        secret: config.secret.clone(),
+        seed: config.seed,
+    };
+    match server::serve(listener, settings) {
+        Ok(()) => Ending::Quit,
+        Err(err) => {
+            eprintln!("The server stopped: {err}");
+            Ending::Error
+        }
+    }
+}
+
+/// Serve games as a JSON API over HTTP until the server fails (or is
+/// stopped with CTRL + C).
+fn serve_http(config: &Config) -> Ending {
+    let listener = match TcpListener::bind(config.address.as_str()) {
+        Ok(listener) => listener,
+        Err(err) => {
+            eprintln!("Could not listen on {}: {err}", config.address);
+            return Ending::Error;
+        }
+    };
+    println!(
+        "Serving guessing games {} at http://{}/games.",
+        config.describe_range(),
+        config.address
+    );
+
+    let settings = ApiSettings {
+        low: config.low,
+        high: config.high,
+        difficulty: config.difficulty,
+        max_attempts: config.max_attempts,
+        secret: config.secret.clone(),
+        seed: config.seed,
+        session_timeout: config.session_timeout,
+        max_connections: http::DEFAULT_MAX_CONNECTIONS,
+        max_games: http::DEFAULT_MAX_GAMES,
+    };
+    match http::serve(listener, settings) {
+        Ok(()) => Ending::Quit,
+        Err(err) => {
+            eprintln!("The server stopped: {err}");
+            Ending::Error
+        }
+    }
+}
+
+fn record_win(config: &Config, game: &Game) -> io::Result<()> {
+    let mut board = Leaderboard::load(&config.leaderboard_path)?;
+    board.add(Entry {
+        name: config.name.clone(),
+        low: game.low(),
+        high: game.high(),
+        difficulty: config.difficulty,
+        attempts: game.attempts(),
+        seconds: game.elapsed().as_secs_f64(),
+        score: game_score(game),
+        date: leaderboard::today(),
+    });
+    board.save(&config.leaderboard_path)
+}
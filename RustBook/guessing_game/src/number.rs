@@ -0,0 +1,416 @@
+use crate::expr::Arithmetic;
+use crate::input::{is_negative_number, GuessError};
+use rand::distributions::uniform::SampleUniform;
+use std::cmp::Ordering;
+use std::fmt;
+use std::num::IntErrorKind;
+
+/// A type the secret number (and the guesses) can have: any of Rust's
+/// integer types, or f32/f64, where a guess within a tolerance of the
+/// secret counts as finding it.
+///
+/// The game, hosts and solvers are generic over Number and default to
+/// u32, the type the book's guessing game uses. Arithmetic lets guesses
+/// be typed as expressions (see `expr::evaluate`).
+pub trait Number:
+    Copy + PartialOrd + fmt::Display + fmt::Debug + SampleUniform + Arithmetic + Send + 'static
+{
+    /// The type's name as typed after --type, such as "i64".
+    const NAME: &'static str;
+
+    const ZERO: Self;
+
+    /// True for f32 and f64, which win within a tolerance.
+    const IS_FLOAT: bool;
+
+    /// What a number of this type looks like, for the message when a
+    /// guess isn't one.
+    const EXAMPLE: &'static str;
+
+    /// Parse a guess (or a bound) typed by the player.
+    fn parse(input: &str) -> Result<Self, GuessError>;
+
+    /// Convert a number from a difficulty preset, if it fits.
+    fn from_u32(n: u32) -> Option<Self>;
+
+    /// The range -d insane plays over: every value of an integer type,
+    /// or -1e9 to 1e9 for floats (their full range is too wide to
+    /// search).
+    fn insane_bounds() -> (Self, Self);
+
+    /// Halfway between `low` and `high`, rounded down, without
+    /// overflowing.
+    fn midpoint(low: Self, high: Self) -> Self;
+
+    /// The number after this one, which is where the secret can start
+    /// after "Too small!". Floats have no next number, so they stay
+    /// put.
+    fn after(self) -> Self;
+
+    /// The number before this one; see `after`.
+    fn before(self) -> Self;
+
+    /// Whether a guess is close enough to the secret to win: equal for
+    /// integers, within `tolerance` for floats.
+    fn close_enough(guess: Self, secret: Self, tolerance: Self) -> bool;
+
+    /// How far apart neighbouring values are in `low..=high` where they
+    /// are furthest apart: 1 for integers, and for floats the gap next
+    /// to whichever end is further from zero.
+    fn spacing(low: Self, high: Self) -> Self;
+
+    /// The fewest guesses that always find a secret in `low..=high`
+    /// (binary search's worst case).
+    fn attempts_needed(low: Self, high: Self, tolerance: Self) -> usize;
+
+    /// How a guess compares with the secret, as the game reports it.
+    fn compare(guess: Self, secret: Self, tolerance: Self) -> Ordering {
+        if Self::close_enough(guess, secret, tolerance) {
+            Ordering::Equal
+        } else if guess < secret {
+            Ordering::Less
+        } else {
+            Ordering::Greater
+        }
+    }
+}
+
+macro_rules! integer {
+    ($($t:ident),*) => {$(
+        impl Number for $t {
+            const NAME: &'static str = stringify!($t);
+            const ZERO: Self = 0;
+            const IS_FLOAT: bool = false;
+            const EXAMPLE: &'static str = if $t::MIN == 0 {
+                "a whole number, such as 42"
+            } else {
+                "a whole number, such as 42 or -7"
+            };
+
+            fn parse(input: &str) -> Result<Self, GuessError> {
+                let input = input.trim();
+                input.parse().map_err(|err: std::num::ParseIntError| match err.kind() {
+                    IntErrorKind::Empty => GuessError::Empty,
+                    IntErrorKind::PosOverflow => GuessError::TooLarge {
+                        input: input.to_string(),
+                        max: $t::MAX.to_string(),
+                    },
+                    IntErrorKind::NegOverflow => GuessError::TooSmall {
+                        input: input.to_string(),
+                        min: $t::MIN.to_string(),
+                    },
+                    _ if is_negative_number(input) => GuessError::Negative(input.to_string()),
+                    _ => GuessError::NotANumber {
+                        input: input.to_string(),
+                        expected: Self::EXAMPLE,
+                    },
+                })
+            }
+
+            fn from_u32(n: u32) -> Option<Self> {
+                $t::try_from(n).ok()
+            }
+
+            fn insane_bounds() -> (Self, Self) {
+                ($t::MIN, $t::MAX)
+            }
+
+            fn midpoint(low: Self, high: Self) -> Self {
+                // (low + high) / 2 could overflow; halving first can't.
+                // The last term puts back the half lost when both are
+                // odd.
+                (low >> 1) + (high >> 1) + (low & high & 1)
+            }
+
+            fn after(self) -> Self {
+                self.saturating_add(1)
+            }
+
+            fn before(self) -> Self {
+                self.saturating_sub(1)
+            }
+
+            fn close_enough(guess: Self, secret: Self, _tolerance: Self) -> bool {
+                guess == secret
+            }
+
+            fn spacing(_low: Self, _high: Self) -> Self {
+                1
+            }
+
+            fn attempts_needed(low: Self, high: Self, _tolerance: Self) -> usize {
+                // The difference always fits in a u128, even for the
+                // full i128 range, when it is worked out modulo 2^128.
+                let span = (high as i128).wrapping_sub(low as i128) as u128;
+                match span.checked_add(1) {
+                    Some(size) => (u128::BITS - size.leading_zeros()) as usize,
+                    // All 2^128 values of a 128-bit type.
+                    None => 129,
+                }
+            }
+        }
+    )*};
+}
+
+integer!(i8, i16, i32, i64, i128, isize, u8, u16, u32, u64, u128, usize);
+
+macro_rules! float {
+    ($($t:ident),*) => {$(
+        impl Number for $t {
+            const NAME: &'static str = stringify!($t);
+            const ZERO: Self = 0.0;
+            const IS_FLOAT: bool = true;
+            const EXAMPLE: &'static str = "a number, such as 42 or 3.5";
+
+            fn parse(input: &str) -> Result<Self, GuessError> {
+                let input = input.trim();
+                if input.is_empty() {
+                    return Err(GuessError::Empty);
+                }
+                match input.parse::<$t>() {
+                    // "inf" and "NaN" parse, but aren't numbers anyone
+                    // can guess; "1e999" overflows to infinity.
+                    Ok(value) if value.is_finite() => Ok(value),
+                    Ok(value) if value == $t::INFINITY && !input.to_ascii_lowercase().contains("inf") => {
+                        Err(GuessError::TooLarge { input: input.to_string(), max: $t::MAX.to_string() })
+                    }
+                    Ok(value) if value == $t::NEG_INFINITY && !input.to_ascii_lowercase().contains("inf") => {
+                        Err(GuessError::TooSmall { input: input.to_string(), min: $t::MIN.to_string() })
+                    }
+                    _ => Err(GuessError::NotANumber {
+                        input: input.to_string(),
+                        expected: Self::EXAMPLE,
+                    }),
+                }
+            }
+
+            fn from_u32(n: u32) -> Option<Self> {
+                Some(n as $t)
+            }
+
+            fn insane_bounds() -> (Self, Self) {
+                (-1e9, 1e9)
+            }
+
+            fn midpoint(low: Self, high: Self) -> Self {
+                low / 2.0 + high / 2.0
+            }
+
+            fn after(self) -> Self {
+                self
+            }
+
+            fn before(self) -> Self {
+                self
+            }
+
+            fn close_enough(guess: Self, secret: Self, tolerance: Self) -> bool {
+                (guess - secret).abs() <= tolerance
+            }
+
+            fn spacing(low: Self, high: Self) -> Self {
+                // The gap below the end further from zero, which is
+                // never zero as low < high, and never infinite.
+                let end = low.abs().max(high.abs());
+                end - $t::from_bits(end.to_bits() - 1)
+            }
+
+            fn attempts_needed(low: Self, high: Self, tolerance: Self) -> usize {
+                // After k wrong guesses binary search has narrowed the
+                // secret down to a width of (high - low) / 2^k, and the
+                // next guess (in the middle) is within tolerance once
+                // that width is at most 2 * tolerance. The logarithms
+                // are taken apart so a tiny tolerance can't make the
+                // ratio infinite; the result is at most a few thousand.
+                let halvings = (f64::from(high) - f64::from(low)).log2() - f64::from(tolerance).log2();
+                halvings.ceil().clamp(1.0, f64::from(u16::MAX)) as usize
+            }
+        }
+    )*};
+}
+
+float!(f32, f64);
+
+/// The default tolerance for float games: a guess within 0.01 of the
+/// secret wins.
+pub const DEFAULT_TOLERANCE: f64 = 0.01;
+
+/// The number types that can be picked with --type.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NumberType {
+    I8,
+    I16,
+    I32,
+    I64,
+    I128,
+    Isize,
+    U8,
+    U16,
+    U32,
+    U64,
+    U128,
+    Usize,
+    F32,
+    F64,
+}
+
+/// Something to do with a number type that is only known at runtime.
+/// `NumberType::visit` calls `visit` with the matching Rust type, so
+/// the code in `visit` is written once and compiled for every type.
+pub trait NumberVisitor {
+    type Output;
+
+    fn visit<T: Number>(self) -> Self::Output;
+}
+
+impl NumberType {
+    pub const ALL: [NumberType; 14] = [
+        NumberType::I8,
+        NumberType::I16,
+        NumberType::I32,
+        NumberType::I64,
+        NumberType::I128,
+        NumberType::Isize,
+        NumberType::U8,
+        NumberType::U16,
+        NumberType::U32,
+        NumberType::U64,
+        NumberType::U128,
+        NumberType::Usize,
+        NumberType::F32,
+        NumberType::F64,
+    ];
+
+    pub fn visit<V: NumberVisitor>(self, visitor: V) -> V::Output {
+        match self {
+            NumberType::I8 => visitor.visit::<i8>(),
+            NumberType::I16 => visitor.visit::<i16>(),
+            NumberType::I32 => visitor.visit::<i32>(),
+            NumberType::I64 => visitor.visit::<i64>(),
+            NumberType::I128 => visitor.visit::<i128>(),
+            NumberType::Isize => visitor.visit::<isize>(),
+            NumberType::U8 => visitor.visit::<u8>(),
+            NumberType::U16 => visitor.visit::<u16>(),
+            NumberType::U32 => visitor.visit::<u32>(),
+            NumberType::U64 => visitor.visit::<u64>(),
+            NumberType::U128 => visitor.visit::<u128>(),
+            NumberType::Usize => visitor.visit::<usize>(),
+            NumberType::F32 => visitor.visit::<f32>(),
+            NumberType::F64 => visitor.visit::<f64>(),
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        struct Name;
+        impl NumberVisitor for Name {
+            type Output = &'static str;
+            fn visit<T: Number>(self) -> &'static str {
+                T::NAME
+            }
+        }
+        self.visit(Name)
+    }
+
+    pub fn from_name(name: &str) -> Option<NumberType> {
+        NumberType::ALL
+            .into_iter()
+            .find(|kind| kind.name().eq_ignore_ascii_case(name))
+    }
+
+    pub fn is_float(&self) -> bool {
+        struct IsFloat;
+        impl NumberVisitor for IsFloat {
+            type Output = bool;
+            fn visit<T: Number>(self) -> bool {
+                T::IS_FLOAT
+            }
+        }
+        self.visit(IsFloat)
+    }
+}
+
+impl fmt::Display for NumberType {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+/// The range and tolerance of a game over some Number type, worked out
+/// from the command line.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Bounds<T> {
+    pub low: T,
+    pub high: T,
+    pub tolerance: T,
+}
+
+/// Work out the range for a game over T: the difficulty preset's
+/// bounds, overridden by --min and --max if given (as text, since they
+/// can be negative or fractional), and for floats the tolerance.
+pub fn bounds<T: Number>(
+    preset: (u32, u32),
+    insane: bool,
+    min: Option<&str>,
+    max: Option<&str>,
+    tolerance: Option<&str>,
+) -> Result<Bounds<T>, String> {
+    let parse = |flag: &str, value: &str| {
+        T::parse(value).map_err(|err| format!("{flag} {value} is not a valid {}: {err}", T::NAME))
+    };
+    let preset_bound = |n: u32| {
+        T::from_u32(n).ok_or_else(|| {
+            format!("the difficulty's range doesn't fit in {}; use --min and --max", T::NAME)
+        })
+    };
+
+    let (preset_low, preset_high) = if insane {
+        T::insane_bounds()
+    } else {
+        (preset_bound(preset.0)?, preset_bound(preset.1)?)
+    };
+    let low = min.map(|value| parse("--min", value)).transpose()?.unwrap_or(preset_low);
+    let high = max.map(|value| parse("--max", value)).transpose()?.unwrap_or(preset_high);
+    if low >= high {
+        return Err(format!("the range {low}..={high} leaves nothing to guess"));
+    }
+    // A float range wider than the type's largest value can't be drawn
+    // from (or halved) without overflowing to infinity.
+    if T::IS_FLOAT && high.checked_sub(low).is_none() {
+        return Err(format!("the range {low:?}..={high:?} is too wide for {}", T::NAME));
+    }
+
+    let spacing = T::spacing(low, high);
+    let tolerance = match tolerance {
+        Some(_) if !T::IS_FLOAT => {
+            return Err(String::from("--tolerance only makes sense with --type f32 or f64"));
+        }
+        Some(value) => parse("--tolerance", value)?,
+        // The default is as fine as the range allows, which for a wide
+        // f32 range can be coarser than DEFAULT_TOLERANCE.
+        None if T::IS_FLOAT => {
+            let default = T::parse(&DEFAULT_TOLERANCE.to_string()).unwrap_or(T::ZERO);
+            if default < spacing {
+                spacing
+            } else {
+                default
+            }
+        }
+        None => T::ZERO,
+    };
+    if T::IS_FLOAT && tolerance <= T::ZERO {
+        return Err(String::from("the tolerance must be more than 0"));
+    }
+    // A tolerance finer than the gaps between the floats in the range
+    // can't be told apart from guessing exactly, and the number of
+    // halvings it would take isn't a number of guesses anyone plays.
+    let halvings = high.checked_sub(low).and_then(|width| width.checked_div(tolerance));
+    if T::IS_FLOAT && (tolerance < spacing || halvings.is_none()) {
+        return Err(format!(
+            "--tolerance {tolerance:?} is finer than {} can tell apart in {low:?}..={high:?}; \
+             use at least {spacing:?}",
+            T::NAME
+        ));
+    }
+
+    Ok(Bounds { low, high, tolerance })
+}
@@ -0,0 +1,384 @@
+use crate::config::Difficulty;
+use crate::ending::Ending;
+use crate::game::Game;
+use crate::input::{parse_guess, GuessError};
+use crate::transcript::Comparison;
+use serde::{Deserialize, Serialize};
+use serde_json::error::Category;
+use std::io::{self, BufRead, Write};
+
+/// The version of the JSON-lines protocol. It is sent in the handshake,
+/// and a request that asks for another version is answered with an
+/// error. Adding fields to a message doesn't change the version;
+/// removing or renaming one does.
+pub const VERSION: u32 = 1;
+
+/// The machine-readable ways of playing picked with --protocol.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Protocol {
+    /// One JSON object per line each way (see `play`).
+    Jsonl,
+}
+
+impl Protocol {
+    pub fn from_name(name: &str) -> Option<Protocol> {
+        match name.to_ascii_lowercase().as_str() {
+            "jsonl" | "json-lines" => Some(Protocol::Jsonl),
+            _ => None,
+        }
+    }
+}
+
+/// What the game is set up with, for the handshake.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Setup {
+    pub difficulty: Option<Difficulty>,
+    /// How the secret is drawn, as given to --secret.
+    pub distribution: String,
+}
+
+/// A line sent by the bot. `version` is optional, but if it is given it
+/// must be VERSION.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Request {
+    #[serde(default)]
+    version: Option<u32>,
+    guess: serde_json::Number,
+}
+
+/// A line sent to the bot. Every message has a "type" saying which of
+/// these it is.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum Message {
+    /// The first line: the protocol version and what the game is.
+    Hello {
+        version: u32,
+        low: u32,
+        high: u32,
+        /// None for unlimited guesses.
+        max_attempts: Option<usize>,
+        /// The preset the range came from, or None for a custom range.
+        difficulty: Option<Difficulty>,
+        distribution: String,
+    },
+    /// The answer to a guess: "less" if the guess is smaller than the
+    /// secret, "greater" if it is bigger, "equal" if it is the secret.
+    Result {
+        result: Comparison,
+        /// How many guesses have been made, counting this one.
+        attempt: usize,
+        remaining: Option<usize>,
+    },
+    /// A line that isn't a usable guess. It doesn't use up an attempt.
+    Error {
+        code: ErrorCode,
+        message: String,
+        /// The line of input it was about, counting from 1.
+        line: usize,
+    },
+    /// The last line: how the game ended, and the secret.
+    End {
+        ending: Ending,
+        attempts: usize,
+        secret: u32,
+    },
+}
+
+/// What was wrong with a request, for bots to act on. The message that
+/// comes with it is for people. The HTTP API (see `http`) answers with
+/// the same codes, and a few of its own.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ErrorCode {
+    /// The line isn't JSON.
+    InvalidJson,
+    /// The line is JSON, but not a `{"guess": n}` object.
+    InvalidRequest,
+    /// The request is for another version of the protocol.
+    UnsupportedVersion,
+    /// The guess isn't a whole number.
+    InvalidGuess,
+    /// The guess is outside the range in the handshake.
+    OutOfRange,
+    /// HTTP only: there is no such page, or no such game (it may have
+    /// expired).
+    NotFound,
+    /// HTTP only: the page doesn't take that method.
+    MethodNotAllowed,
+    /// HTTP only: the game is already won or lost.
+    GameOver,
+    /// HTTP only: the options for a new game don't make a game.
+    InvalidOptions,
+    /// HTTP only: the request body is too big.
+    TooLarge,
+    /// HTTP only: the server already has as many connections or games
+    /// as it takes.
+    Busy,
+}
+
+/// Play `game` with a bot over JSON lines: a `hello` message first,
+/// then a `result` (or an `error`) for every line read from `input`,
+/// such as `{"guess": 50}`, and an `end` message once the game is won
+/// or lost, or `input` is closed. Blank lines are skipped. Nothing but
+/// these messages is written to `output`.
+///
+/// With `max_errors`, the game ends with `Ending::Error` once that many
+/// `error` messages have been sent, so a bot stuck sending lines that
+/// aren't guesses can't keep it going forever.
+pub fn play(
+    game: &mut Game,
+    setup: &Setup,
+    input: impl BufRead,
+    mut output: impl Write,
+    max_errors: Option<usize>,
+) -> io::Result<Ending> {
+    send(
+        &mut output,
+        &Message::Hello {
+            version: VERSION,
+            low: game.low(),
+            high: game.high(),
+            max_attempts: game.max_attempts(),
+            difficulty: setup.difficulty,
+            distribution: setup.distribution.clone(),
+        },
+    )?;
+
+    let mut ending = Ending::Quit;
+    let mut errors = 0;
+    for (index, line) in input.lines().enumerate() {
+        let guess = match line {
+            Ok(line) if line.trim().is_empty() => continue,
+            Ok(line) => read_guess(&line, game.low(), game.high()),
+            // A line that isn't UTF-8 has still been read, so the game
+            // can carry on with the next one.
+            Err(err) if err.kind() == io::ErrorKind::InvalidData => {
+                Err((ErrorCode::InvalidJson, String::from("the line is not valid UTF-8")))
+            }
+            Err(_) => {
+                ending = Ending::Error;
+                break;
+            }
+        };
+        let guess = match guess {
+            Ok(guess) => guess,
+            Err((code, message)) => {
+                send(&mut output, &Message::Error { code, message, line: index + 1 })?;
+                errors += 1;
+                if max_errors.is_some_and(|max_errors| errors >= max_errors) {
+                    ending = Ending::Error;
+                    break;
+                }
+                continue;
+            }
+        };
+
+        let outcome = game.submit_guess(guess);
+        send(
+            &mut output,
+            &Message::Result {
+                result: outcome.ordering.into(),
+                attempt: outcome.attempts,
+                remaining: outcome.remaining,
+            },
+        )?;
+        if outcome.is_win() {
+            ending = Ending::Won;
+            break;
+        }
+        if outcome.is_loss() {
+            ending = Ending::Lost;
+            break;
+        }
+    }
+
+    send(
+        &mut output,
+        &Message::End {
+            ending,
+            attempts: game.attempts(),
+            secret: game.secret(),
+        },
+    )?;
+    Ok(ending)
+}
+
+/// The guess in a `{"guess": n}` request, or the error to send back.
+pub(crate) fn read_guess(line: &str, low: u32, high: u32) -> Result<u32, (ErrorCode, String)> {
+    let request: Request = serde_json::from_str(line).map_err(|err| match err.classify() {
+        Category::Data => (ErrorCode::InvalidRequest, format!("expected {{\"guess\": n}}: {err}")),
+        _ => (ErrorCode::InvalidJson, err.to_string()),
+    })?;
+    if let Some(version) = request.version.filter(|&version| version != VERSION) {
+        return Err((
+            ErrorCode::UnsupportedVersion,
+            format!("this game speaks version {VERSION} of the protocol, not {version}"),
+        ));
+    }
+
+    // The number goes through the same checks as a typed guess, so the
+    // messages match the line-by-line game's.
+    parse_guess(&request.guess.to_string(), low, high).map_err(|err| {
+        let code = match err {
+            GuessError::Negative(_)
+            | GuessError::TooLarge { .. }
+            | GuessError::TooSmall { .. }
+            | GuessError::OutOfRange { .. } => ErrorCode::OutOfRange,
+            _ => ErrorCode::InvalidGuess,
+        };
+        (code, err.to_string())
+    })
+}
+
+/// Write one message as a line of JSON, flushing it straight away so a
+/// bot waiting for the answer gets it.
+fn send(output: &mut impl Write, message: &Message) -> io::Result<()> {
+    serde_json::to_writer(&mut *output, message)?;
+    writeln!(output)?;
+    output.flush()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::{json, Value};
+
+    fn to_json(message: &Message) -> Value {
+        serde_json::to_value(message).unwrap()
+    }
+
+    #[test]
+    fn messages_are_tagged_with_their_type() {
+        let hello = Message::Hello {
+            version: VERSION,
+            low: 1,
+            high: 100,
+            max_attempts: None,
+            difficulty: Some(Difficulty::Easy),
+            distribution: String::from("uniform"),
+        };
+        assert_eq!(
+            to_json(&hello),
+            json!({
+                "type": "hello",
+                "version": 1,
+                "low": 1,
+                "high": 100,
+                "max_attempts": null,
+                "difficulty": "easy",
+                "distribution": "uniform",
+            })
+        );
+
+        let result = Message::Result {
+            result: Comparison::Less,
+            attempt: 3,
+            remaining: Some(4),
+        };
+        assert_eq!(to_json(&result), json!({"type": "result", "result": "less", "attempt": 3, "remaining": 4}));
+
+        let error = Message::Error {
+            code: ErrorCode::OutOfRange,
+            message: String::from("no"),
+            line: 2,
+        };
+        assert_eq!(to_json(&error), json!({"type": "error", "code": "out_of_range", "message": "no", "line": 2}));
+
+        let end = Message::End {
+            ending: Ending::Won,
+            attempts: 5,
+            secret: 42,
+        };
+        assert_eq!(to_json(&end), json!({"type": "end", "ending": "won", "attempts": 5, "secret": 42}));
+    }
+
+    #[test]
+    fn error_codes_read_back() {
+        let codes = [
+            (ErrorCode::InvalidJson, "invalid_json"),
+            (ErrorCode::InvalidRequest, "invalid_request"),
+            (ErrorCode::UnsupportedVersion, "unsupported_version"),
+            (ErrorCode::InvalidGuess, "invalid_guess"),
+            (ErrorCode::OutOfRange, "out_of_range"),
+            (ErrorCode::NotFound, "not_found"),
+            (ErrorCode::MethodNotAllowed, "method_not_allowed"),
+            (ErrorCode::GameOver, "game_over"),
+            (ErrorCode::InvalidOptions, "invalid_options"),
+            (ErrorCode::TooLarge, "too_large"),
+            (ErrorCode::Busy, "busy"),
+        ];
+        for (code, name) in codes {
+            assert_eq!(serde_json::to_value(code).unwrap(), json!(name));
+            assert_eq!(serde_json::from_value::<ErrorCode>(json!(name)).unwrap(), code);
+        }
+    }
+
+    /// The code read_guess answers `line` with, between 1 and 100.
+    fn code(line: &str) -> ErrorCode {
+        read_guess(line, 1, 100).unwrap_err().0
+    }
+
+    #[test]
+    fn requests_are_read() {
+        assert_eq!(read_guess(r#"{"guess": 50}"#, 1, 100), Ok(50));
+        assert_eq!(read_guess(r#" {"version": 1, "guess": 7} "#, 1, 100), Ok(7));
+        assert_eq!(code(r#"{"guess": 50"#), ErrorCode::InvalidJson);
+        assert_eq!(code("50"), ErrorCode::InvalidRequest);
+        assert_eq!(code(r#"{"guess": "50"}"#), ErrorCode::InvalidRequest);
+        assert_eq!(code(r#"{"guess": 50, "hint": true}"#), ErrorCode::InvalidRequest);
+        assert_eq!(code(r#"{"version": 2, "guess": 50}"#), ErrorCode::UnsupportedVersion);
+        assert_eq!(code(r#"{"guess": 2.5}"#), ErrorCode::InvalidGuess);
+        assert_eq!(code(r#"{"guess": -5}"#), ErrorCode::OutOfRange);
+        assert_eq!(code(r#"{"guess": 101}"#), ErrorCode::OutOfRange);
+        assert_eq!(code(r#"{"guess": 99999999999}"#), ErrorCode::OutOfRange);
+    }
+
+    fn setup() -> Setup {
+        Setup {
+            difficulty: None,
+            distribution: String::from("uniform"),
+        }
+    }
+
+    /// Play a game whose secret is 42 with `input`, returning the
+    /// messages sent as JSON and the ending.
+    fn session(input: &str, max_errors: Option<usize>) -> (Vec<Value>, Ending) {
+        let mut game = Game::with_secret(1, 100, 42).with_max_attempts(Some(5));
+        let mut output = Vec::new();
+        let ending = play(&mut game, &setup(), input.as_bytes(), &mut output, max_errors).unwrap();
+        let messages = String::from_utf8(output)
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        (messages, ending)
+    }
+
+    #[test]
+    fn a_game_is_played_over_lines() {
+        let (messages, ending) = session("{\"guess\": 50}\n\nnope\n{\"guess\": 42}\n{\"guess\": 1}\n", None);
+        assert_eq!(ending, Ending::Won);
+        let types: Vec<&str> = messages.iter().map(|message| message["type"].as_str().unwrap()).collect();
+        assert_eq!(types, ["hello", "result", "error", "result", "end"]);
+        assert_eq!(messages[0]["max_attempts"], 5);
+        assert_eq!(messages[1]["result"], "greater");
+        // Blank lines are skipped but still counted.
+        assert_eq!((&messages[2]["code"], &messages[2]["line"]), (&json!("invalid_json"), &json!(3)));
+        assert_eq!(messages[3], json!({"type": "result", "result": "equal", "attempt": 2, "remaining": 3}));
+        assert_eq!(messages[4], json!({"type": "end", "ending": "won", "attempts": 2, "secret": 42}));
+    }
+
+    #[test]
+    fn a_closed_input_quits_and_too_many_errors_stop_the_game() {
+        let (messages, ending) = session("{\"guess\": 10}\n", None);
+        assert_eq!(ending, Ending::Quit);
+        assert_eq!(messages.last().unwrap()["ending"], "quit");
+
+        let (messages, ending) = session("x\nx\nx\n{\"guess\": 42}\n", Some(2));
+        assert_eq!(ending, Ending::Error);
+        assert_eq!(messages.len(), 4);
+        assert_eq!(messages[3]["attempts"], 0);
+    }
+}
@@ -0,0 +1,240 @@
+use crate::solver::{BinarySearch, Solver};
+use std::cmp::Ordering;
+use std::fmt;
+
+/// What the player says about the program's guess in reverse mode.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Answer {
+    /// The player's number is higher than the guess.
+    Higher,
+    /// The player's number is lower than the guess.
+    Lower,
+    Correct,
+}
+
+impl Answer {
+    /// Read an answer typed at the prompt. The first letter is enough.
+    pub fn parse(input: &str) -> Option<Answer> {
+        match input.trim().to_ascii_lowercase().as_str() {
+            "higher" | "h" => Some(Answer::Higher),
+            "lower" | "l" => Some(Answer::Lower),
+            "correct" | "c" => Some(Answer::Correct),
+            _ => None,
+        }
+    }
+
+    /// The answer as the Ordering of `guess.cmp(&secret)`, the same
+    /// value the Game hands back when the program holds the secret: the
+    /// number being higher means the guess was too small (`Less`).
+    pub fn ordering(&self) -> Ordering {
+        match self {
+            Answer::Higher => Ordering::Less,
+            Answer::Lower => Ordering::Greater,
+            Answer::Correct => Ordering::Equal,
+        }
+    }
+}
+
+/// The player's answers contradict each other, so there is no number
+/// they could be thinking of.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Inconsistency {
+    pub answer: Answer,
+    pub guess: u32,
+    /// The biggest guess the player said their number was higher than,
+    /// if any.
+    pub higher_than: Option<u32>,
+    /// The smallest guess the player said their number was lower than,
+    /// if any.
+    pub lower_than: Option<u32>,
+    /// The range the player agreed to pick from.
+    pub low: u32,
+    pub high: u32,
+}
+
+impl fmt::Display for Inconsistency {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match (self.answer, self.higher_than, self.lower_than) {
+            (Answer::Higher, _, Some(lower_than)) => write!(
+                f,
+                "you said higher than {} and lower than {lower_than}",
+                self.guess
+            ),
+            (Answer::Higher, _, None) => write!(
+                f,
+                "you said higher than {}, but the number is at most {}",
+                self.guess, self.high
+            ),
+            (Answer::Lower, Some(higher_than), _) => write!(
+                f,
+                "you said higher than {higher_than} and lower than {}",
+                self.guess
+            ),
+            (Answer::Lower, None, _) => write!(
+                f,
+                "you said lower than {}, but the number is at least {}",
+                self.guess, self.low
+            ),
+            // The program only ever guesses numbers that are still
+            // possible, so "correct" can't contradict anything.
+            (Answer::Correct, _, _) => write!(f, "you said {} was correct", self.guess),
+        }
+    }
+}
+
+/// The program's side of reverse mode: it guesses with binary search
+/// and keeps track of what the player's answers rule out.
+pub struct Guesser {
+    solver: BinarySearch,
+    low: u32,
+    high: u32,
+    // The numbers that are still possible, given the answers so far.
+    possible_low: u32,
+    possible_high: u32,
+    higher_than: Option<u32>,
+    lower_than: Option<u32>,
+    attempts: usize,
+}
+
+impl Guesser {
+    pub fn new(low: u32, high: u32) -> Guesser {
+        Guesser {
+            solver: BinarySearch::new(low, high),
+            low,
+            high,
+            possible_low: low,
+            possible_high: high,
+            higher_than: None,
+            lower_than: None,
+            attempts: 0,
+        }
+    }
+
+    pub fn next_guess(&mut self) -> u32 {
+        self.attempts += 1;
+        self.solver.next_guess()
+    }
+
+    pub fn attempts(&self) -> usize {
+        self.attempts
+    }
+
+    /// Record the player's answer to `guess`. If the answer leaves no
+    /// number the player could be thinking of, they have cheated (or
+    /// made a mistake) and the answer is rejected.
+    pub fn answer(&mut self, guess: u32, answer: Answer) -> Result<(), Inconsistency> {
+        let consistent = match answer {
+            Answer::Higher => guess < self.possible_high,
+            Answer::Lower => guess > self.possible_low,
+            Answer::Correct => (self.possible_low..=self.possible_high).contains(&guess),
+        };
+        if !consistent {
+            return Err(Inconsistency {
+                answer,
+                guess,
+                higher_than: self.higher_than,
+                lower_than: self.lower_than,
+                low: self.low,
+                high: self.high,
+            });
+        }
+
+        match answer {
+            Answer::Higher => {
+                self.possible_low = guess + 1;
+                self.higher_than = Some(guess);
+            }
+            Answer::Lower => {
+                self.possible_high = guess - 1;
+                self.lower_than = Some(guess);
+            }
+            Answer::Correct => {}
+        }
+        self.solver.feedback(guess, answer.ordering());
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Give the guesser `answers` in turn, returning its guesses and
+    /// the first inconsistency, if any.
+    fn play(low: u32, high: u32, answers: &[Answer]) -> (Vec<u32>, Option<Inconsistency>) {
+        let mut guesser = Guesser::new(low, high);
+        let mut guesses = Vec::new();
+        for &answer in answers {
+            let guess = guesser.next_guess();
+            guesses.push(guess);
+            if let Err(inconsistency) = guesser.answer(guess, answer) {
+                return (guesses, Some(inconsistency));
+            }
+        }
+        (guesses, None)
+    }
+
+    #[test]
+    fn honest_answers_find_every_number() {
+        for number in 1..=100 {
+            let mut guesser = Guesser::new(1, 100);
+            loop {
+                let guess = guesser.next_guess();
+                let answer = match number.cmp(&guess) {
+                    Ordering::Greater => Answer::Higher,
+                    Ordering::Less => Answer::Lower,
+                    Ordering::Equal => Answer::Correct,
+                };
+                assert_eq!(guesser.answer(guess, answer), Ok(()));
+                if answer == Answer::Correct {
+                    break;
+                }
+            }
+            assert!(guesser.attempts() <= 7, "{number} took {}", guesser.attempts());
+        }
+    }
+
+    #[test]
+    fn higher_and_lower_can_leave_nothing_between_them() {
+        use Answer::{Higher, Lower};
+        let (guesses, inconsistency) = play(1, 100, &[Higher, Lower, Lower, Lower, Lower, Higher, Lower]);
+        assert_eq!(guesses, [50, 75, 62, 56, 53, 51, 52]);
+        let inconsistency = inconsistency.unwrap();
+        assert_eq!((inconsistency.higher_than, inconsistency.lower_than), (Some(51), Some(53)));
+        assert_eq!(inconsistency.to_string(), "you said higher than 51 and lower than 52");
+
+        let (_, inconsistency) = play(1, 100, &[Higher, Lower, Lower, Lower, Lower, Higher, Higher]);
+        assert_eq!(inconsistency.unwrap().to_string(), "you said higher than 52 and lower than 53");
+    }
+
+    #[test]
+    fn answers_past_the_ends_of_the_range_are_caught() {
+        let (guesses, inconsistency) = play(1, 10, &[Answer::Higher; 5]);
+        assert_eq!(guesses, [5, 8, 9, 10]);
+        let inconsistency = inconsistency.unwrap();
+        assert_eq!(inconsistency.lower_than, None);
+        assert_eq!(inconsistency.to_string(), "you said higher than 10, but the number is at most 10");
+
+        let (guesses, inconsistency) = play(1, 10, &[Answer::Lower; 5]);
+        assert_eq!(guesses, [5, 2, 1]);
+        assert_eq!(inconsistency.unwrap().to_string(), "you said lower than 1, but the number is at least 1");
+    }
+
+    #[test]
+    fn a_rejected_answer_changes_nothing() {
+        let mut guesser = Guesser::new(1, 10);
+        assert!(guesser.answer(5, Answer::Higher).is_ok());
+        assert!(guesser.answer(5, Answer::Correct).is_err());
+        assert!(guesser.answer(6, Answer::Lower).is_err());
+        assert_eq!(guesser.answer(6, Answer::Correct), Ok(()));
+    }
+
+    #[test]
+    fn answers_can_be_abbreviated() {
+        assert_eq!(Answer::parse(" H\n"), Some(Answer::Higher));
+        assert_eq!(Answer::parse("lower"), Some(Answer::Lower));
+        assert_eq!(Answer::parse("C"), Some(Answer::Correct));
+        assert_eq!(Answer::parse("maybe"), None);
+    }
+}
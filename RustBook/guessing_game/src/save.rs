@@ -0,0 +1,187 @@
+use crate::config::{Config, Difficulty};
+use crate::feedback::FeedbackMode;
+use crate::game::Game;
+use crate::leaderboard;
+use rand::rngs::StdRng;
+use rand::{Rng, RngCore, SeedableRng};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// The save file format. Loading a save with another version is
+/// refused rather than guessed at.
+pub const VERSION: u32 = 1;
+
+/// Mixed into the salt of every save to hide the secret (see
+/// `SavedGame::secret`).
+const KEY: u64 = 0x6775_6573_735f_6b65;
+
+/// A game that was stopped part way through, as written to the save
+/// file: enough to carry on exactly where the player left off.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SavedGame {
+    pub version: u32,
+    pub low: u32,
+    pub high: u32,
+    /// The preset the range came from, or None for a custom range.
+    pub difficulty: Option<Difficulty>,
+    pub max_attempts: Option<usize>,
+    pub feedback: FeedbackMode,
+    pub guesses: Vec<u32>,
+    /// How long the game had been going when it was saved.
+    pub seconds: f64,
+    /// The state of the random number generator, as a 32-byte seed in
+    /// hex, so a resumed game draws the same numbers it would have.
+    pub rng: String,
+    /// The secret, hidden so it can't just be read off the file: a
+    /// random salt, the secret XORed with a mask drawn from a generator
+    /// seeded with the salt and a key, and a checksum over the secret
+    /// and the rest of the game, so editing the guesses or the secret
+    /// is caught on loading. This stops peeking, not anyone willing to
+    /// read the source.
+    pub secret: String,
+}
+
+impl SavedGame {
+    /// Save `game`, which was set up from `config`. `rng` is the game's
+    /// random number generator; its state is carried over by drawing a
+    /// seed for the resumed game from it.
+    pub fn capture(game: &Game, config: &Config, rng: &mut dyn RngCore) -> SavedGame {
+        let mut seed = [0u8; 32];
+        rng.fill_bytes(&mut seed);
+
+        let mut saved = SavedGame {
+            version: VERSION,
+            low: game.low(),
+            high: game.high(),
+            difficulty: config.difficulty,
+            max_attempts: game.max_attempts(),
+            feedback: config.feedback,
+            guesses: game.history().to_vec(),
+            seconds: game.elapsed().as_secs_f64(),
+            rng: to_hex(&seed),
+            secret: String::new(),
+        };
+        let salt: u64 = rand::thread_rng().gen();
+        saved.secret = saved.hide(game.secret(), salt);
+        saved
+    }
+
+    /// Rebuild the game and its random number generator. Fails if the
+    /// file is from another version or has been tampered with.
+    pub fn restore(&self) -> Result<(Game, StdRng), String> {
+        if self.version != VERSION {
+            return Err(format!(
+                "the save is version {}, but this game reads version {VERSION}",
+                self.version
+            ));
+        }
+        let secret = self.reveal()?;
+        let seed: [u8; 32] = from_hex(&self.rng)
+            .and_then(|bytes| bytes.try_into().ok())
+            .ok_or("the random number generator state is damaged")?;
+        let elapsed = Duration::try_from_secs_f64(self.seconds)
+            .map_err(|_| format!("{} is not a valid playing time", self.seconds))?;
+
+        let game = Game::with_secret(self.low, self.high, secret)
+            .with_max_attempts(self.max_attempts)
+            .resume(self.guesses.clone(), elapsed);
+        Ok((game, StdRng::from_seed(seed)))
+    }
+
+    /// Write the save to `path`, atomically like the leaderboard.
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        leaderboard::write_json(path, self)
+    }
+
+    pub fn load(path: &Path) -> io::Result<SavedGame> {
+        let contents = fs::read_to_string(path)?;
+        Ok(serde_json::from_str(&contents)?)
+    }
+
+    /// The salt, the masked secret and the checksum, as hex separated
+    /// by dashes.
+    fn hide(&self, secret: u32, salt: u64) -> String {
+        let (mask, check_key) = keys(salt);
+        format!(
+            "{salt:016x}-{:08x}-{:016x}",
+            secret ^ mask,
+            self.checksum(secret, check_key)
+        )
+    }
+
+    fn reveal(&self) -> Result<u32, String> {
+        let tampered = || String::from("the save has been tampered with");
+        let mut parts = self.secret.split('-');
+        let (Some(salt), Some(masked), Some(check), None) =
+            (parts.next(), parts.next(), parts.next(), parts.next())
+        else {
+            return Err(tampered());
+        };
+        let salt = u64::from_str_radix(salt, 16).map_err(|_| tampered())?;
+        let masked = u32::from_str_radix(masked, 16).map_err(|_| tampered())?;
+        let check = u64::from_str_radix(check, 16).map_err(|_| tampered())?;
+
+        let (mask, check_key) = keys(salt);
+        let secret = masked ^ mask;
+        if self.checksum(secret, check_key) != check {
+            return Err(tampered());
+        }
+        Ok(secret)
+    }
+
+    /// FNV-1a over the key and everything that decides how the game
+    /// plays out. std's hasher isn't used because its output may change
+    /// between Rust releases, which would break old saves.
+    fn checksum(&self, secret: u32, key: u64) -> u64 {
+        let mut bytes = Vec::new();
+        bytes.extend(key.to_le_bytes());
+        bytes.extend(secret.to_le_bytes());
+        bytes.extend(self.low.to_le_bytes());
+        bytes.extend(self.high.to_le_bytes());
+        bytes.extend((self.max_attempts.map_or(0, |max| max as u64 + 1)).to_le_bytes());
+        for guess in &self.guesses {
+            bytes.extend(guess.to_le_bytes());
+        }
+        bytes.extend(self.seconds.to_bits().to_le_bytes());
+        bytes.extend(self.difficulty.map_or("custom", |difficulty| difficulty.name()).as_bytes());
+        bytes.extend(self.rng.as_bytes());
+
+        bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, &byte| {
+            (hash ^ u64::from(byte)).wrapping_mul(0x0000_0100_0000_01b3)
+        })
+    }
+}
+
+/// The mask for the secret and the key for the checksum, both drawn
+/// from a generator seeded with the salt and KEY.
+fn keys(salt: u64) -> (u32, u64) {
+    let mut rng = StdRng::seed_from_u64(salt ^ KEY);
+    (rng.next_u32(), rng.next_u64())
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{byte:02x}")).collect()
+}
+
+fn from_hex(hex: &str) -> Option<Vec<u8>> {
+    if !hex.len().is_multiple_of(2) || !hex.is_ascii() {
+        return None;
+    }
+    (0..hex.len())
+        .step_by(2)
+        .map(|index| u8::from_str_radix(&hex[index..index + 2], 16).ok())
+        .collect()
+}
+
+/// Where games are saved unless --save-file says otherwise: next to
+/// the leaderboard, as guessing_game/save.json under the user's data
+/// directory.
+pub fn default_path() -> PathBuf {
+    dirs::data_dir()
+        .map(|dir| dir.join("guessing_game"))
+        .unwrap_or_default()
+        .join("save.json")
+}
@@ -0,0 +1,30 @@
+use crate::game::{optimal_attempts, Game};
+use std::time::Duration;
+
+/// Points for finding the secret. A bigger range is worth more, using
+/// fewer attempts is worth more, and taking longer is worth less:
+///
+/// score = 100 * log2(range size) * (optimal attempts / attempts used)
+///             * (30 / (30 + seconds taken))
+///
+/// so a perfect binary search on 1..=100 played in no time at all
+/// scores about 664. Lost games score nothing.
+pub fn score(attempts: usize, range_size: u64, elapsed: Duration) -> u64 {
+    if attempts == 0 || range_size < 2 {
+        return 0;
+    }
+
+    let bits = (range_size as f64).log2();
+    let efficiency = (optimal_attempts(range_size) as f64 / attempts as f64).min(1.0);
+    let speed = 30.0 / (30.0 + elapsed.as_secs_f64());
+
+    (100.0 * bits * efficiency * speed).round() as u64
+}
+
+/// The score for a finished game, or 0 if it was not won.
+pub fn game_score(game: &Game) -> u64 {
+    if !game.is_won() {
+        return 0;
+    }
+    score(game.attempts(), game.range_size(), game.elapsed())
+}
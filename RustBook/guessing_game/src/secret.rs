@@ -0,0 +1,689 @@
+use rand::{Rng, RngCore};
+use std::f64::consts::PI;
+
+/// Where secret numbers come from.
+///
+/// Every source draws from an inclusive range, but not necessarily
+/// uniformly: some numbers can be more likely than others, and some can
+/// never come up at all. Besides drawing secrets, a source can say how
+/// likely each part of the range is, so a solver that knows the
+/// distribution can make use of it (see `solver::WeightedSearch`).
+pub trait SecretSource {
+    /// What the secret is, for the banner, such as "a prime number".
+    fn describe(&self) -> String;
+
+    fn low(&self) -> u32;
+
+    fn high(&self) -> u32;
+
+    /// Draw a secret.
+    fn draw(&self, rng: &mut dyn RngCore) -> u32;
+
+    /// How likely the secret is to be in `low..=high`, relative to the
+    /// other parts of the range. Only comparisons between masses from
+    /// the same source mean anything; they needn't add up to 1.
+    fn mass(&self, low: u32, high: u32) -> f64;
+
+    /// How likely the secret is to be `n`; 0 if it can never be.
+    fn weight(&self, n: u32) -> f64 {
+        self.mass(n, n)
+    }
+
+    /// How many numbers in `low..=high` the source can draw. Sources
+    /// that can't count them quickly may overestimate, never
+    /// underestimate.
+    fn count(&self, low: u32, high: u32) -> u64 {
+        match clamp(self, low, high) {
+            Some((low, high)) => u64::from(high) - u64::from(low) + 1,
+            None => 0,
+        }
+    }
+}
+
+/// The distributions that can be picked with --secret.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Distribution {
+    /// Every number in the range is equally likely.
+    Uniform,
+    /// A bell curve around the middle of the range.
+    Normal,
+    /// Likelihood rises in a straight line to the middle and falls off
+    /// again.
+    Triangular,
+    /// Only prime numbers, all equally likely.
+    Primes,
+    /// Only even numbers.
+    Even,
+    /// Only odd numbers.
+    Odd,
+    /// One of the given numbers. A number listed twice is twice as
+    /// likely.
+    List(Vec<u32>),
+}
+
+impl Distribution {
+    /// Read a --secret value: a distribution's name, or `list:` and a
+    /// comma separated list of numbers.
+    pub fn from_name(name: &str) -> Result<Distribution, String> {
+        if let Some(list) = name.strip_prefix("list:") {
+            let values = list
+                .split(',')
+                .map(|value| {
+                    value
+                        .trim()
+                        .parse()
+                        .map_err(|_| format!("'{}' in the secret list is not a number", value.trim()))
+                })
+                .collect::<Result<Vec<u32>, String>>()?;
+            return Ok(Distribution::List(values));
+        }
+
+        match name.to_ascii_lowercase().as_str() {
+            "uniform" => Ok(Distribution::Uniform),
+            "normal" => Ok(Distribution::Normal),
+            "triangular" => Ok(Distribution::Triangular),
+            "prime" | "primes" => Ok(Distribution::Primes),
+            "even" => Ok(Distribution::Even),
+            "odd" => Ok(Distribution::Odd),
+            _ => Err(format!("unknown secret distribution '{name}'")),
+        }
+    }
+
+    /// The distribution as it would be typed after --secret, so
+    /// `from_name` reads it back.
+    pub fn name(&self) -> String {
+        match self {
+            Distribution::Uniform => String::from("uniform"),
+            Distribution::Normal => String::from("normal"),
+            Distribution::Triangular => String::from("triangular"),
+            Distribution::Primes => String::from("primes"),
+            Distribution::Even => String::from("even"),
+            Distribution::Odd => String::from("odd"),
+            Distribution::List(values) => {
+                let values: Vec<String> = values.iter().map(u32::to_string).collect();
+                format!("list:{}", values.join(","))
+            }
+        }
+    }
+
+    /// A source drawing from `low..=high` with this distribution, or
+    /// an error if the range has no number the distribution allows.
+    pub fn source(&self, low: u32, high: u32) -> Result<Box<dyn SecretSource + Send>, String> {
+        Ok(match self {
+            Distribution::Uniform => Box::new(Uniform { low, high }),
+            Distribution::Normal => Box::new(Normal::new(low, high)),
+            Distribution::Triangular => Box::new(Triangular { low, high }),
+            Distribution::Primes => Box::new(Primes::new(low, high)?),
+            Distribution::Even => Box::new(Parity::new(low, high, false)?),
+            Distribution::Odd => Box::new(Parity::new(low, high, true)?),
+            Distribution::List(values) => Box::new(List::new(low, high, values)?),
+        })
+    }
+}
+
+/// The overlap of `low..=high` with a source's range, if any.
+fn clamp<S: SecretSource + ?Sized>(source: &S, low: u32, high: u32) -> Option<(u32, u32)> {
+    let (low, high) = (low.max(source.low()), high.min(source.high()));
+    (low <= high).then_some((low, high))
+}
+
+/// rand's gen_range on every number in the range.
+pub struct Uniform {
+    low: u32,
+    high: u32,
+}
+
+impl SecretSource for Uniform {
+    fn describe(&self) -> String {
+        String::from("any number in the range")
+    }
+
+    fn low(&self) -> u32 {
+        self.low
+    }
+
+    fn high(&self) -> u32 {
+        self.high
+    }
+
+    fn draw(&self, rng: &mut dyn RngCore) -> u32 {
+        rng.gen_range(self.low..=self.high)
+    }
+
+    fn mass(&self, low: u32, high: u32) -> f64 {
+        match clamp(self, low, high) {
+            Some((low, high)) => (u64::from(high) - u64::from(low) + 1) as f64,
+            None => 0.0,
+        }
+    }
+}
+
+// The continuous distributions below spread their probability over the
+// real interval [low, high + 1), and number n gets whatever falls in
+// [n, n + 1). For a narrow interval, the difference of two values of
+// the cumulative distribution close to 1 loses all its precision, so
+// there the mass comes from the density in the middle instead.
+fn continuous_mass(
+    source: &dyn SecretSource,
+    low: u32,
+    high: u32,
+    cdf: impl Fn(f64) -> f64,
+    density: impl Fn(f64) -> f64,
+) -> f64 {
+    let Some((low, high)) = clamp(source, low, high) else {
+        return 0.0;
+    };
+    let (from, to) = (f64::from(low), f64::from(high) + 1.0);
+    let size = f64::from(source.high()) - f64::from(source.low()) + 1.0;
+
+    if to - from < size * 1e-4 {
+        density((from + to) / 2.0) * (to - from)
+    } else {
+        cdf(to) - cdf(from)
+    }
+}
+
+/// A bell curve centred on the middle of the range, with the range
+/// reaching three standard deviations either side, cut off at the ends.
+pub struct Normal {
+    low: u32,
+    high: u32,
+    mean: f64,
+    deviation: f64,
+}
+
+impl Normal {
+    fn new(low: u32, high: u32) -> Normal {
+        let (from, to) = (f64::from(low), f64::from(high) + 1.0);
+        Normal {
+            low,
+            high,
+            mean: (from + to) / 2.0,
+            deviation: (to - from) / 6.0,
+        }
+    }
+
+    fn density(&self, x: f64) -> f64 {
+        let z = (x - self.mean) / self.deviation;
+        (-z * z / 2.0).exp() / (self.deviation * (2.0 * PI).sqrt())
+    }
+
+    fn cdf(&self, x: f64) -> f64 {
+        (1.0 + erf((x - self.mean) / (self.deviation * 2f64.sqrt()))) / 2.0
+    }
+}
+
+impl SecretSource for Normal {
+    fn describe(&self) -> String {
+        format!("most likely near {:.0}, less so further away", self.mean)
+    }
+
+    fn low(&self) -> u32 {
+        self.low
+    }
+
+    fn high(&self) -> u32 {
+        self.high
+    }
+
+    fn draw(&self, rng: &mut dyn RngCore) -> u32 {
+        // The Box-Muller transform turns two uniform numbers into a
+        // normally distributed one. Draws that land outside the range
+        // are thrown away, which happens about 0.3% of the time.
+        loop {
+            let u1: f64 = 1.0 - rng.gen::<f64>();
+            let u2: f64 = rng.gen();
+            let z = (-2.0 * u1.ln()).sqrt() * (2.0 * PI * u2).cos();
+            let x = self.mean + self.deviation * z;
+            if x >= f64::from(self.low) && x < f64::from(self.high) + 1.0 {
+                return x as u32;
+            }
+        }
+    }
+
+    fn mass(&self, low: u32, high: u32) -> f64 {
+        continuous_mass(self, low, high, |x| self.cdf(x), |x| self.density(x))
+    }
+}
+
+/// The error function, using the approximation from Abramowitz and
+/// Stegun (7.1.26), which is good to about 1.5e-7.
+fn erf(x: f64) -> f64 {
+    let t = 1.0 / (1.0 + 0.327_591_1 * x.abs());
+    let polynomial = t
+        * (0.254_829_592
+            + t * (-0.284_496_736 + t * (1.421_413_741 + t * (-1.453_152_027 + t * 1.061_405_429))));
+    let y = 1.0 - polynomial * (-x * x).exp();
+    if x < 0.0 {
+        -y
+    } else {
+        y
+    }
+}
+
+/// Likelihood rises in a straight line from the ends of the range to
+/// its middle, like the total of two dice.
+pub struct Triangular {
+    low: u32,
+    high: u32,
+}
+
+impl Triangular {
+    fn bounds(&self) -> (f64, f64, f64) {
+        let (from, to) = (f64::from(self.low), f64::from(self.high) + 1.0);
+        (from, to, (from + to) / 2.0)
+    }
+
+    fn density(&self, x: f64) -> f64 {
+        let (from, to, peak) = self.bounds();
+        let height = 2.0 / (to - from);
+        if x < peak {
+            height * (x - from) / (peak - from)
+        } else {
+            height * (to - x) / (to - peak)
+        }
+    }
+
+    fn cdf(&self, x: f64) -> f64 {
+        let (from, to, peak) = self.bounds();
+        if x <= peak {
+            (x - from).powi(2) / ((to - from) * (peak - from))
+        } else {
+            1.0 - (to - x).powi(2) / ((to - from) * (to - peak))
+        }
+    }
+}
+
+impl SecretSource for Triangular {
+    fn describe(&self) -> String {
+        let (_, _, peak) = self.bounds();
+        format!("more likely the closer it is to {peak:.0}")
+    }
+
+    fn low(&self) -> u32 {
+        self.low
+    }
+
+    fn high(&self) -> u32 {
+        self.high
+    }
+
+    fn draw(&self, rng: &mut dyn RngCore) -> u32 {
+        // The average of two uniform numbers has a triangular
+        // distribution.
+        let (from, to, _) = self.bounds();
+        let x = from + (to - from) * (rng.gen::<f64>() + rng.gen::<f64>()) / 2.0;
+        (x as u32).min(self.high)
+    }
+
+    fn mass(&self, low: u32, high: u32) -> f64 {
+        continuous_mass(self, low, high, |x| self.cdf(x), |x| self.density(x))
+    }
+}
+
+/// Every prime in the range, equally likely.
+pub struct Primes {
+    low: u32,
+    high: u32,
+}
+
+/// Ranges at most this big have their primes counted one by one; for
+/// bigger ones the count is estimated with the prime number theorem.
+const PRIME_COUNT_LIMIT: u32 = 4_096;
+
+/// No two primes below 2^32 are more than this far apart, so any range
+/// at least this big has a prime in it.
+const MAX_PRIME_GAP: u32 = 336;
+
+impl Primes {
+    fn new(low: u32, high: u32) -> Result<Primes, String> {
+        let has_prime = high - low >= MAX_PRIME_GAP || (low..=high).any(is_prime);
+        if !has_prime {
+            return Err(format!("there are no primes between {low} and {high}"));
+        }
+        Ok(Primes { low, high })
+    }
+}
+
+impl SecretSource for Primes {
+    fn describe(&self) -> String {
+        String::from("a prime number")
+    }
+
+    fn low(&self) -> u32 {
+        self.low
+    }
+
+    fn high(&self) -> u32 {
+        self.high
+    }
+
+    fn draw(&self, rng: &mut dyn RngCore) -> u32 {
+        // Near 2^32 about one number in 22 is prime, so this doesn't
+        // take long.
+        loop {
+            let n = rng.gen_range(self.low..=self.high);
+            if is_prime(n) {
+                return n;
+            }
+        }
+    }
+
+    fn mass(&self, low: u32, high: u32) -> f64 {
+        let Some((low, high)) = clamp(self, low, high) else {
+            return 0.0;
+        };
+        if high - low < PRIME_COUNT_LIMIT {
+            (low..=high).filter(|&n| is_prime(n)).count() as f64
+        } else {
+            // Around x, roughly one number in ln(x) is prime.
+            let middle = (f64::from(low) + f64::from(high)) / 2.0;
+            (f64::from(high) - f64::from(low) + 1.0) / middle.ln()
+        }
+    }
+
+    fn weight(&self, n: u32) -> f64 {
+        if is_prime(n) && clamp(self, n, n).is_some() {
+            1.0
+        } else {
+            0.0
+        }
+    }
+
+    fn count(&self, low: u32, high: u32) -> u64 {
+        match clamp(self, low, high) {
+            Some((low, high)) if high - low < PRIME_COUNT_LIMIT => {
+                (low..=high).filter(|&n| is_prime(n)).count() as u64
+            }
+            Some((low, high)) => u64::from(high) - u64::from(low) + 1,
+            None => 0,
+        }
+    }
+}
+
+/// Miller-Rabin with the bases 2, 7 and 61, which is exact for every
+/// u32.
+pub fn is_prime(n: u32) -> bool {
+    if n < 2 {
+        return false;
+    }
+    for small in [2, 3, 5, 7, 11, 13, 61] {
+        if n.is_multiple_of(small) {
+            return n == small;
+        }
+    }
+
+    let n = u64::from(n);
+    let power = |mut base: u64, mut exponent: u64| {
+        let mut result = 1;
+        base %= n;
+        while exponent > 0 {
+            if exponent & 1 == 1 {
+                result = result * base % n;
+            }
+            base = base * base % n;
+            exponent >>= 1;
+        }
+        result
+    };
+
+    let mut odd = n - 1;
+    let mut twos = 0;
+    while odd.is_multiple_of(2) {
+        odd /= 2;
+        twos += 1;
+    }
+
+    [2, 7, 61].into_iter().all(|base| {
+        let mut x = power(base, odd);
+        if x == 1 || x == n - 1 {
+            return true;
+        }
+        for _ in 1..twos {
+            x = x * x % n;
+            if x == n - 1 {
+                return true;
+            }
+        }
+        false
+    })
+}
+
+/// Only the even (or only the odd) numbers in the range.
+pub struct Parity {
+    low: u32,
+    high: u32,
+    odd: bool,
+}
+
+impl Parity {
+    fn new(low: u32, high: u32, odd: bool) -> Result<Parity, String> {
+        let parity = Parity { low, high, odd };
+        if parity.count(low, high) == 0 {
+            let kind = if odd { "odd" } else { "even" };
+            return Err(format!("there are no {kind} numbers between {low} and {high}"));
+        }
+        Ok(parity)
+    }
+
+    /// The first number in `low..=high` with the right parity, if any.
+    fn first(&self, low: u32, high: u32) -> Option<u32> {
+        let first = if (low % 2 == 1) == self.odd { low } else { low.checked_add(1)? };
+        (first <= high).then_some(first)
+    }
+
+    fn count(&self, low: u32, high: u32) -> u64 {
+        match self.first(low, high) {
+            Some(first) => u64::from(high - first) / 2 + 1,
+            None => 0,
+        }
+    }
+}
+
+impl SecretSource for Parity {
+    fn describe(&self) -> String {
+        String::from(if self.odd { "an odd number" } else { "an even number" })
+    }
+
+    fn low(&self) -> u32 {
+        self.low
+    }
+
+    fn high(&self) -> u32 {
+        self.high
+    }
+
+    fn draw(&self, rng: &mut dyn RngCore) -> u32 {
+        // new() checked there is at least one.
+        let first = self.first(self.low, self.high).unwrap_or(self.low);
+        let count = self.count(self.low, self.high);
+        first + 2 * rng.gen_range(0..count) as u32
+    }
+
+    fn mass(&self, low: u32, high: u32) -> f64 {
+        SecretSource::count(self, low, high) as f64
+    }
+
+    fn count(&self, low: u32, high: u32) -> u64 {
+        match clamp(self, low, high) {
+            Some((low, high)) => Parity::count(self, low, high),
+            None => 0,
+        }
+    }
+}
+
+/// One of a list of numbers the player gave.
+pub struct List {
+    low: u32,
+    high: u32,
+    // Sorted, so the numbers in a part of the range can be counted with
+    // a binary search.
+    values: Vec<u32>,
+}
+
+impl List {
+    fn new(low: u32, high: u32, values: &[u32]) -> Result<List, String> {
+        if values.is_empty() {
+            return Err(String::from("the secret list is empty"));
+        }
+        if let Some(value) = values.iter().find(|&&value| value < low || value > high) {
+            return Err(format!("{value} in the secret list is not between {low} and {high}"));
+        }
+
+        let mut values = values.to_vec();
+        values.sort_unstable();
+        Ok(List { low, high, values })
+    }
+}
+
+impl SecretSource for List {
+    fn describe(&self) -> String {
+        let mut values = self.values.clone();
+        values.dedup();
+        let values: Vec<String> = values.iter().map(u32::to_string).collect();
+        format!("one of {}", values.join(", "))
+    }
+
+    fn low(&self) -> u32 {
+        self.low
+    }
+
+    fn high(&self) -> u32 {
+        self.high
+    }
+
+    fn draw(&self, rng: &mut dyn RngCore) -> u32 {
+        self.values[rng.gen_range(0..self.values.len())]
+    }
+
+    fn mass(&self, low: u32, high: u32) -> f64 {
+        let from = self.values.partition_point(|&value| value < low);
+        let to = self.values.partition_point(|&value| value <= high);
+        to.saturating_sub(from) as f64
+    }
+
+    fn count(&self, low: u32, high: u32) -> u64 {
+        let from = self.values.partition_point(|&value| value < low);
+        let to = self.values.partition_point(|&value| value <= high);
+        let mut values = self.values[from..to.max(from)].to_vec();
+        values.dedup();
+        values.len() as u64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::seeded_rng;
+
+    /// Draw `count` secrets from `distribution` over `low..=high`.
+    fn draws(distribution: &Distribution, low: u32, high: u32, count: usize) -> Vec<u32> {
+        let source = distribution.source(low, high).unwrap();
+        let mut rng = seeded_rng(Some(1));
+        (0..count).map(|_| source.draw(&mut rng)).collect()
+    }
+
+    #[test]
+    fn small_primes_are_found() {
+        let primes: Vec<u32> = (0..50).filter(|&n| is_prime(n)).collect();
+        assert_eq!(primes, [2, 3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37, 41, 43, 47]);
+        assert!(is_prime(61) && !is_prime(61 * 61));
+    }
+
+    #[test]
+    fn carmichael_numbers_are_not_prime() {
+        for n in [561, 1105, 1729, 2465, 2821, 6601, 8911, 10585, 15841, 29341, 41041, 62745] {
+            assert!(!is_prime(n), "{n}");
+        }
+        // Strong pseudoprimes to some of the bases.
+        for n in [2047, 3277, 4033, 4681, 25326001, 3215031751] {
+            assert!(!is_prime(n), "{n}");
+        }
+    }
+
+    #[test]
+    fn primes_near_the_top_of_u32_are_found() {
+        assert!(is_prime(4294967291));
+        assert!(is_prime(4294967279));
+        assert!(!is_prime(4294967295));
+        assert!(!is_prime(4294967293));
+        assert!(!is_prime(4294967294));
+        // 65521 is the biggest prime below 2^16, so its square is the
+        // biggest prime square below 2^32.
+        assert!(!is_prime(65521 * 65521));
+    }
+
+    #[test]
+    fn every_distribution_draws_in_range() {
+        for distribution in [Distribution::Uniform, Distribution::Normal, Distribution::Triangular] {
+            for n in draws(&distribution, 10, 20, 500) {
+                assert!((10..=20).contains(&n), "{} drew {n}", distribution.name());
+            }
+        }
+        for n in draws(&Distribution::Uniform, u32::MAX - 3, u32::MAX, 100) {
+            assert!(n >= u32::MAX - 3);
+        }
+    }
+
+    #[test]
+    fn primes_are_drawn_only_in_range() {
+        for n in draws(&Distribution::Primes, 90, 120, 500) {
+            assert!(is_prime(n) && (90..=120).contains(&n), "{n}");
+        }
+        for n in draws(&Distribution::Primes, u32::MAX - 100, u32::MAX, 50) {
+            assert!(is_prime(n) && n >= u32::MAX - 100, "{n}");
+        }
+    }
+
+    #[test]
+    fn parity_is_kept() {
+        for n in draws(&Distribution::Even, 3, 9, 200) {
+            assert!(n % 2 == 0 && (4..=8).contains(&n), "{n}");
+        }
+        for n in draws(&Distribution::Odd, 2, 10, 200) {
+            assert!(n % 2 == 1 && (3..=9).contains(&n), "{n}");
+        }
+        // The ends of u32 don't overflow.
+        assert_eq!(draws(&Distribution::Even, u32::MAX - 1, u32::MAX, 10), [u32::MAX - 1; 10]);
+        assert_eq!(draws(&Distribution::Odd, u32::MAX - 1, u32::MAX, 10), [u32::MAX; 10]);
+    }
+
+    #[test]
+    fn lists_draw_only_their_values() {
+        let list = Distribution::List(vec![7, 3, 7]);
+        let drawn = draws(&list, 1, 10, 300);
+        assert!(drawn.iter().all(|n| [3, 7].contains(n)));
+        assert!(drawn.contains(&3) && drawn.contains(&7));
+        let source = list.source(1, 10).unwrap();
+        assert_eq!(source.describe(), "one of 3, 7");
+        assert_eq!((source.weight(7), source.weight(3), source.weight(5)), (2.0, 1.0, 0.0));
+        assert_eq!(source.count(1, 10), 2);
+    }
+
+    #[test]
+    fn empty_sets_are_rejected() {
+        let error = |distribution: Distribution, low, high| distribution.source(low, high).err().unwrap();
+        assert_eq!(error(Distribution::Primes, 24, 28), "there are no primes between 24 and 28");
+        assert_eq!(error(Distribution::Primes, 0, 1), "there are no primes between 0 and 1");
+        assert_eq!(error(Distribution::Even, 3, 3), "there are no even numbers between 3 and 3");
+        assert_eq!(
+            error(Distribution::Odd, u32::MAX - 1, u32::MAX - 1),
+            format!("there are no odd numbers between {0} and {0}", u32::MAX - 1)
+        );
+        assert_eq!(error(Distribution::List(vec![]), 1, 10), "the secret list is empty");
+        assert_eq!(
+            error(Distribution::List(vec![5, 11]), 1, 10),
+            "11 in the secret list is not between 1 and 10"
+        );
+    }
+
+    #[test]
+    fn names_read_back() {
+        for name in ["uniform", "normal", "triangular", "primes", "even", "odd", "list:3,1,3"] {
+            assert_eq!(Distribution::from_name(name).unwrap().name(), name);
+        }
+        assert_eq!(Distribution::from_name("prime"), Ok(Distribution::Primes));
+        assert!(Distribution::from_name("list:1,x").is_err());
+        assert!(Distribution::from_name("poisson").is_err());
+    }
+}
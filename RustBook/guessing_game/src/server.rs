@@ -0,0 +1,373 @@
+use crate::game::{seeded_rng, Game};
+use crate::input::{parse_guess, Command};
+use crate::secret::{Distribution, SecretSource};
+use rand::rngs::StdRng;
+use std::collections::HashMap;
+use std::io::{self, BufRead, BufReader, Write};
+use std::net::{Shutdown, TcpListener, TcpStream, ToSocketAddrs};
+use std::sync::mpsc::{self, SyncSender, TrySendError};
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+/// The address the server listens on (and the client connects to) by
+/// default, the same one the Rust book uses for its web server.
+pub const DEFAULT_ADDRESS: &str = "127.0.0.1:7878";
+
+/// How every room on the server plays.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RoomSettings {
+    pub low: u32,
+    pub high: u32,
+    pub max_attempts: Option<usize>,
+    /// How the secrets are drawn.
+    pub secret: Distribution,
+    /// Seed for the secrets, so a server can be restarted with the same
+    /// sequence of secrets.
+    pub seed: Option<u64>,
+}
+
+/// Host rooms of the guessing game over TCP, one thread per client,
+/// until the listener fails.
+///
+/// The protocol is plain lines of text, so any line-based client works
+/// (including `guessing_game connect` and netcat). A client joins a
+/// room with `join <room> <name>`, then every number it sends is a
+/// guess at the room's secret. Everyone in the room sees every guess
+/// and whether it was too small or too big; the first to find the
+/// secret wins the round and a new round starts with a new secret.
+/// `rooms` lists the rooms and `quit` disconnects.
+pub fn serve(listener: TcpListener, settings: RoomSettings) -> io::Result<()> {
+    let source = settings
+        .secret
+        .source(settings.low, settings.high)
+        .map_err(|err| io::Error::new(io::ErrorKind::InvalidInput, err))?;
+    let lobby = Arc::new(Mutex::new(Lobby {
+        rooms: HashMap::new(),
+        rng: seeded_rng(settings.seed),
+        source,
+        settings,
+        next_id: 0,
+    }));
+
+    for stream in listener.incoming() {
+        let stream = stream?;
+        let lobby = Arc::clone(&lobby);
+        thread::spawn(move || {
+            // A client that disconnects halfway through a write is not
+            // a problem for anyone else, so the error is dropped here.
+            let _ = handle_client(stream, lobby);
+        });
+    }
+
+    Ok(())
+}
+
+/// A bare-bones line-based client: everything typed on standard input
+/// is sent to the server, and everything the server sends is printed.
+/// Returns when the server closes the connection.
+pub fn connect(address: impl ToSocketAddrs) -> io::Result<()> {
+    let stream = TcpStream::connect(address)?;
+    let mut to_server = stream.try_clone()?;
+
+    // Standard input is copied to the server on its own thread so that
+    // messages about other players' guesses show up straight away,
+    // rather than only after we press enter.
+    thread::spawn(move || {
+        let _ = io::copy(&mut io::stdin().lock(), &mut to_server);
+        let _ = to_server.shutdown(Shutdown::Write);
+    });
+
+    let mut from_server = stream;
+    io::copy(&mut from_server, &mut io::stdout().lock())?;
+    Ok(())
+}
+
+/// How many lines can wait to be sent to a client. A client that falls
+/// this far behind is disconnected rather than holding anyone up.
+const OUTBOX_SIZE: usize = 256;
+
+/// The lines on their way to one client. A thread of the client's own
+/// writes them to its socket, so nothing is written while the lobby is
+/// locked, and a client that stops reading only stalls itself.
+#[derive(Clone)]
+struct Outbox {
+    lines: SyncSender<String>,
+    stream: Arc<TcpStream>,
+}
+
+impl Outbox {
+    /// Start the writer thread for `stream`. It stops once every Outbox
+    /// for the client has been dropped, or a write fails.
+    fn open(stream: &TcpStream) -> io::Result<Outbox> {
+        let mut writer = stream.try_clone()?;
+        let (lines, queue) = mpsc::sync_channel::<String>(OUTBOX_SIZE);
+        thread::spawn(move || {
+            for line in queue {
+                if writeln!(writer, "{line}").is_err() {
+                    break;
+                }
+            }
+            // Closing the connection also ends the client's reading
+            // thread if the write failed.
+            let _ = writer.shutdown(Shutdown::Both);
+        });
+        Ok(Outbox {
+            lines,
+            stream: Arc::new(stream.try_clone()?),
+        })
+    }
+
+    /// Queue a line for the client. If the queue is full, the client is
+    /// disconnected.
+    fn send(&self, line: String) -> io::Result<()> {
+        match self.lines.try_send(line) {
+            Ok(()) => Ok(()),
+            Err(TrySendError::Full(_)) => {
+                let _ = self.stream.shutdown(Shutdown::Both);
+                Err(io::Error::new(io::ErrorKind::TimedOut, "the client fell too far behind"))
+            }
+            Err(TrySendError::Disconnected(_)) => Err(io::ErrorKind::BrokenPipe.into()),
+        }
+    }
+}
+
+struct Lobby {
+    rooms: HashMap<String, Room>,
+    rng: StdRng,
+    source: Box<dyn SecretSource + Send>,
+    settings: RoomSettings,
+    next_id: usize,
+}
+
+struct Room {
+    round: usize,
+    secret: u32,
+    members: Vec<Member>,
+}
+
+struct Member {
+    id: usize,
+    name: String,
+    outbox: Outbox,
+    // Every member has their own Game holding the room's secret, so
+    // attempts are counted per player.
+    game: Game,
+}
+
+impl Room {
+    fn new(lobby_rng: &mut StdRng, source: &dyn SecretSource) -> Room {
+        Room {
+            round: 1,
+            secret: source.draw(lobby_rng),
+            members: Vec::new(),
+        }
+    }
+
+    /// Send a line to everyone in the room. This only queues the line,
+    /// so it never waits on a slow client. Members whose connection has
+    /// gone away are dropped when their own thread notices.
+    fn broadcast(&mut self, message: &str) {
+        for member in &self.members {
+            let _ = member.outbox.send(message.to_string());
+        }
+    }
+
+    fn new_game(&self, settings: &RoomSettings) -> Game {
+        Game::with_secret(settings.low, settings.high, self.secret)
+            .with_max_attempts(settings.max_attempts)
+    }
+
+    /// If nobody in the room can still guess, reveal the secret and
+    /// start the next round. Returns whether it did.
+    fn end_round_if_everyone_is_out(
+        &mut self,
+        rng: &mut StdRng,
+        source: &dyn SecretSource,
+        settings: &RoomSettings,
+    ) -> bool {
+        if self.members.is_empty() || !self.members.iter().all(|member| member.game.is_over()) {
+            return false;
+        }
+        self.broadcast(&format!(
+            "Everyone is out of attempts. The secret number was {}.",
+            self.secret
+        ));
+        self.next_round(rng, source, settings);
+        true
+    }
+
+    /// Pick a new secret and reset everyone's attempts.
+    fn next_round(&mut self, rng: &mut StdRng, source: &dyn SecretSource, settings: &RoomSettings) {
+        self.round += 1;
+        self.secret = source.draw(rng);
+        for index in 0..self.members.len() {
+            self.members[index].game = self.new_game(settings);
+        }
+        self.broadcast(&format!(
+            "Round {}: guess the number between {} and {}!",
+            self.round, settings.low, settings.high
+        ));
+    }
+}
+
+fn handle_client(stream: TcpStream, lobby: Arc<Mutex<Lobby>>) -> io::Result<()> {
+    let id = {
+        let mut lobby = lobby.lock().unwrap();
+        lobby.next_id += 1;
+        lobby.next_id
+    };
+    let mut room_name: Option<String> = None;
+
+    // However the client goes away (quit, closing the connection, or a
+    // failed read or write), it leaves its room, so it doesn't stay
+    // behind as a ghost holding on to its name.
+    let result = play_lines(stream, &lobby, id, &mut room_name);
+    if let Some(room_name) = room_name {
+        let mut lobby = lobby.lock().unwrap();
+        let Lobby { rooms, rng, source, settings, .. } = &mut *lobby;
+        leave_room(rooms, &room_name, id, rng, source.as_ref(), settings);
+    }
+    result
+}
+
+/// Answer a client's lines until it quits or the connection fails,
+/// keeping `room_name` up to date with the room it is in.
+fn play_lines(
+    stream: TcpStream,
+    lobby: &Mutex<Lobby>,
+    id: usize,
+    room_name: &mut Option<String>,
+) -> io::Result<()> {
+    let outbox = Outbox::open(&stream)?;
+    let reader = BufReader::new(stream);
+
+    outbox.send(String::from("Welcome to the guessing game! Type \"join <room> <name>\" to play."))?;
+
+    for line in reader.lines() {
+        let line = line?;
+        let line = line.trim();
+
+        if let Some(Command::Quit) = Command::parse(line) {
+            break;
+        }
+
+        let mut lobby = lobby.lock().unwrap();
+        let Lobby { rooms, rng, source, settings, .. } = &mut *lobby;
+
+        if line.eq_ignore_ascii_case("rooms") {
+            if rooms.is_empty() {
+                outbox.send(String::from("There are no rooms yet."))?;
+            }
+            for (name, room) in rooms.iter() {
+                outbox.send(format!("{name}: {} players, round {}", room.members.len(), room.round))?;
+            }
+            continue;
+        }
+
+        if let Some(rest) = line.strip_prefix("join ") {
+            let mut parts = rest.trim().splitn(2, ' ');
+            let (Some(new_room), Some(name)) = (parts.next(), parts.next()) else {
+                outbox.send(String::from("error: usage is \"join <room> <name>\""))?;
+                continue;
+            };
+            let name = name.trim().to_string();
+
+            // The name is checked first, so a join that fails leaves the
+            // client where it was and doesn't open an empty room.
+            let taken = rooms.get(new_room).is_some_and(|room| {
+                room.members.iter().any(|member| member.name == name && member.id != id)
+            });
+            if taken {
+                outbox.send(format!("error: someone in {new_room} is already called {name}"))?;
+                continue;
+            }
+
+            if let Some(old_room) = room_name.take() {
+                leave_room(rooms, &old_room, id, rng, source.as_ref(), settings);
+            }
+
+            let room = rooms
+                .entry(new_room.to_string())
+                .or_insert_with(|| Room::new(rng, source.as_ref()));
+            let game = room.new_game(settings);
+            room.members.push(Member {
+                id,
+                name: name.clone(),
+                outbox: outbox.clone(),
+                game,
+            });
+            room.broadcast(&format!(
+                "{name} joined {new_room} ({} players).",
+                room.members.len()
+            ));
+            outbox.send(format!(
+                "Round {}: guess the number between {} and {}!",
+                room.round, settings.low, settings.high
+            ))?;
+            *room_name = Some(new_room.to_string());
+            continue;
+        }
+
+        let Some(room) = room_name.as_ref().and_then(|name| rooms.get_mut(name)) else {
+            outbox.send(String::from("error: join a room first with \"join <room> <name>\""))?;
+            continue;
+        };
+
+        let guess = match parse_guess(line, settings.low, settings.high) {
+            Ok(guess) => guess,
+            Err(err) => {
+                outbox.send(format!("error: {err}"))?;
+                continue;
+            }
+        };
+
+        let member = room.members
+            .iter_mut()
+            .find(|member| member.id == id)
+            .expect("a client is always a member of the room it joined");
+        if member.game.is_over() {
+            outbox.send(String::from("error: you are out of attempts until the next round"))?;
+            continue;
+        }
+
+        let name = member.name.clone();
+        let outcome = member.game.submit_guess(guess);
+        room.broadcast(&format!("{name} guessed {guess}: {}", outcome.message()));
+
+        if outcome.is_win() {
+            room.broadcast(&format!(
+                "{name} wins round {} in {} guesses!",
+                room.round, outcome.attempts
+            ));
+            room.next_round(rng, source.as_ref(), settings);
+        } else if !room.end_round_if_everyone_is_out(rng, source.as_ref(), settings) && outcome.is_loss() {
+            outbox.send(String::from("You are out of attempts until the next round."))?;
+        }
+    }
+    Ok(())
+}
+
+/// Take a client out of a room, telling the others, and close the room
+/// once it is empty. If the client was the last one still guessing,
+/// the round ends, as it would have after their last guess.
+fn leave_room(
+    rooms: &mut HashMap<String, Room>,
+    room_name: &str,
+    id: usize,
+    rng: &mut StdRng,
+    source: &dyn SecretSource,
+    settings: &RoomSettings,
+) {
+    let Some(room) = rooms.get_mut(room_name) else {
+        return;
+    };
+
+    if let Some(index) = room.members.iter().position(|member| member.id == id) {
+        let member = room.members.remove(index);
+        room.broadcast(&format!("{} left {room_name}.", member.name));
+        room.end_round_if_everyone_is_out(rng, source, settings);
+    }
+    if room.members.is_empty() {
+        rooms.remove(room_name);
+    }
+}
@@ -0,0 +1,323 @@
+use crate::game::{optimal_attempts, Host, Outcome};
+use crate::number::Number;
+use crate::secret::SecretSource;
+use rand::rngs::StdRng;
+use rand::Rng;
+use std::cmp::Ordering;
+
+/// A strategy the computer uses to play the guessing game.
+///
+/// The game loop asks the solver for a guess, submits it to the Game,
+/// and hands back the Ordering the game answered with (`Less` means the
+/// guess was too small, `Greater` too big), exactly as a human player
+/// would read "Too small!" or "Too big!".
+pub trait Solver<T: Number = u32> {
+    /// A short name for the strategy, such as "binary".
+    fn name(&self) -> &str;
+
+    /// The next number to guess.
+    fn next_guess(&mut self) -> T;
+
+    /// Tell the solver how its last guess compared to the secret.
+    fn feedback(&mut self, guess: T, ordering: Ordering);
+}
+
+/// Always guess the middle of the numbers that are still possible. This
+/// never needs more than floor(log2(n)) + 1 = ceil(log2(n + 1)) guesses
+/// for n numbers, which is the best any strategy can guarantee. For
+/// floats it halves the interval until the middle is within the
+/// tolerance of the secret.
+#[derive(Debug, Clone)]
+pub struct BinarySearch<T = u32> {
+    low: T,
+    high: T,
+}
+
+impl<T: Number> BinarySearch<T> {
+    pub fn new(low: T, high: T) -> BinarySearch<T> {
+        BinarySearch { low, high }
+    }
+}
+
+impl<T: Number> Solver<T> for BinarySearch<T> {
+    fn name(&self) -> &str {
+        "binary"
+    }
+
+    fn next_guess(&mut self) -> T {
+        // Number::midpoint rather than (low + high) / 2, which could
+        // overflow near the top of the insane range.
+        T::midpoint(self.low, self.high)
+    }
+
+    fn feedback(&mut self, guess: T, ordering: Ordering) {
+        match ordering {
+            Ordering::Less => self.low = guess.after(),
+            Ordering::Greater => self.high = guess.before(),
+            Ordering::Equal => {
+                self.low = guess;
+                self.high = guess;
+            }
+        }
+    }
+}
+
+/// Guess a random number among those still possible. Each answer
+/// still rules out everything on the wrong side, so it always finishes,
+/// but the average number of guesses grows like 2 ln(n) (about 1.39
+/// log2(n)) instead of log2(n).
+#[derive(Debug, Clone)]
+pub struct RandomSearch {
+    low: u32,
+    high: u32,
+    rng: StdRng,
+}
+
+impl RandomSearch {
+    pub fn new(low: u32, high: u32, rng: StdRng) -> RandomSearch {
+        RandomSearch { low, high, rng }
+    }
+}
+
+impl Solver for RandomSearch {
+    fn name(&self) -> &str {
+        "random"
+    }
+
+    fn next_guess(&mut self) -> u32 {
+        self.rng.gen_range(self.low..=self.high)
+    }
+
+    fn feedback(&mut self, guess: u32, ordering: Ordering) {
+        narrow(&mut self.low, &mut self.high, guess, ordering);
+    }
+}
+
+/// Count up from the lowest number: n guesses in the worst case, which
+/// is what binary search is measured against.
+#[derive(Debug, Clone)]
+pub struct LinearSearch {
+    next: u32,
+}
+
+impl LinearSearch {
+    pub fn new(low: u32) -> LinearSearch {
+        LinearSearch { next: low }
+    }
+}
+
+impl Solver for LinearSearch {
+    fn name(&self) -> &str {
+        "linear"
+    }
+
+    fn next_guess(&mut self) -> u32 {
+        self.next
+    }
+
+    fn feedback(&mut self, guess: u32, ordering: Ordering) {
+        if ordering == Ordering::Less {
+            self.next = guess.saturating_add(1);
+        }
+    }
+}
+
+/// Guess at the golden section of the numbers still possible, about
+/// 38.2% of the way up, as golden-section search does to find the
+/// minimum of a function. Against a guessing game the uneven split
+/// costs guesses: about 1.44 log2(n) in the worst case.
+#[derive(Debug, Clone)]
+pub struct GoldenSection {
+    low: u32,
+    high: u32,
+}
+
+impl GoldenSection {
+    pub fn new(low: u32, high: u32) -> GoldenSection {
+        GoldenSection { low, high }
+    }
+}
+
+/// 1 - 1/φ, the smaller part of a line cut at the golden ratio.
+const GOLDEN_SECTION: f64 = 0.381_966_011_250_105;
+
+impl Solver for GoldenSection {
+    fn name(&self) -> &str {
+        "golden"
+    }
+
+    fn next_guess(&mut self) -> u32 {
+        let width = f64::from(self.high - self.low);
+        self.low + (width * GOLDEN_SECTION).round() as u32
+    }
+
+    fn feedback(&mut self, guess: u32, ordering: Ordering) {
+        narrow(&mut self.low, &mut self.high, guess, ordering);
+    }
+}
+
+/// Shrink low..=high to the side of `guess` the secret is on.
+fn narrow(low: &mut u32, high: &mut u32, guess: u32, ordering: Ordering) {
+    match ordering {
+        Ordering::Less => *low = guess.saturating_add(1),
+        Ordering::Greater => *high = guess.saturating_sub(1),
+        Ordering::Equal => {
+            *low = guess;
+            *high = guess;
+        }
+    }
+}
+
+/// Binary search that knows how the secret was drawn: instead of the
+/// middle of the numbers still possible, it guesses their weighted
+/// median, so each answer halves the *chance* left rather than the
+/// count. Against a skewed distribution that takes fewer guesses on
+/// average, and it never guesses a number the source can't draw when
+/// it can help it.
+///
+/// Given the attempt limit, it also keeps every guess close enough to
+/// the middle that the attempts left are always enough for a plain
+/// binary search, so it can't lose where BinarySearch would win.
+pub struct WeightedSearch<'a> {
+    source: &'a dyn SecretSource,
+    low: u32,
+    high: u32,
+    remaining: Option<usize>,
+}
+
+/// How far from the weighted median WeightedSearch looks for a number
+/// the source can actually draw.
+const SNAP_DISTANCE: u32 = 1_000;
+
+impl<'a> WeightedSearch<'a> {
+    pub fn new(source: &'a dyn SecretSource, max_attempts: Option<usize>) -> WeightedSearch<'a> {
+        WeightedSearch {
+            source,
+            low: source.low(),
+            high: source.high(),
+            remaining: max_attempts,
+        }
+    }
+
+    /// The smallest number at which at least half of the weight left
+    /// lies at or below it.
+    fn weighted_median(&self) -> u32 {
+        let total = self.source.mass(self.low, self.high);
+        if total <= 0.0 {
+            return self.low + (self.high - self.low) / 2;
+        }
+
+        let (mut low, mut high) = (self.low, self.high);
+        while low < high {
+            let mid = low + (high - low) / 2;
+            if self.source.mass(self.low, mid) * 2.0 >= total {
+                high = mid;
+            } else {
+                low = mid + 1;
+            }
+        }
+        low
+    }
+
+    /// The first number in low..=high for which `test` holds, given
+    /// that it holds for every number after that too (or high if it
+    /// holds for none).
+    fn first(&self, test: impl Fn(u32) -> bool) -> u32 {
+        let (mut low, mut high) = (self.low, self.high);
+        while low < high {
+            let mid = low + (high - low) / 2;
+            if test(mid) {
+                high = mid;
+            } else {
+                low = mid + 1;
+            }
+        }
+        low
+    }
+
+    /// The nearest number to `guess` that the source can draw, if there
+    /// is one within SNAP_DISTANCE.
+    fn snap(&self, guess: u32) -> u32 {
+        for distance in 0..=SNAP_DISTANCE {
+            let candidates = [guess.checked_add(distance), guess.checked_sub(distance)];
+            for candidate in candidates.into_iter().flatten() {
+                if candidate >= self.low && candidate <= self.high && self.source.weight(candidate) > 0.0 {
+                    return candidate;
+                }
+            }
+        }
+        guess
+    }
+}
+
+impl Solver for WeightedSearch<'_> {
+    fn name(&self) -> &str {
+        "weighted"
+    }
+
+    fn next_guess(&mut self) -> u32 {
+        let guess = self.snap(self.weighted_median());
+
+        // With r attempts left, the game can always be finished if
+        // neither side of the guess holds more than 2^(r-1) - 1 numbers
+        // the source can draw, so keep the guess inside the window that
+        // allows. If there are too many numbers for that, no strategy
+        // is safe and the weighted median is as good as any.
+        let Some(remaining) = self.remaining else {
+            return guess;
+        };
+        if optimal_attempts(self.source.count(self.low, self.high)) > remaining {
+            return guess;
+        }
+        let side = (1u64 << (remaining - 1).min(63)) - 1;
+        let above = |guess: u32| match guess.checked_add(1) {
+            Some(next) if next <= self.high => self.source.count(next, self.high),
+            _ => 0,
+        };
+        let below = |guess: u32| match guess.checked_sub(1) {
+            Some(previous) if previous >= self.low => self.source.count(self.low, previous),
+            _ => 0,
+        };
+        let lowest = self.first(|guess| above(guess) <= side);
+        let highest = self.first(|guess| below(guess) > side).saturating_sub(1);
+        if below(self.high) <= side {
+            guess.max(lowest)
+        } else {
+            guess.clamp(lowest, highest.max(lowest))
+        }
+    }
+
+    fn feedback(&mut self, guess: u32, ordering: Ordering) {
+        self.remaining = self.remaining.map(|remaining| remaining.saturating_sub(1));
+        match ordering {
+            Ordering::Less => self.low = guess.saturating_add(1),
+            Ordering::Greater => self.high = guess.saturating_sub(1),
+            Ordering::Equal => {
+                self.low = guess;
+                self.high = guess;
+            }
+        }
+    }
+}
+
+/// Let a solver play against a host (usually a Game) until it wins or
+/// runs out of attempts, calling `on_guess` after every guess (e.g. to
+/// print it). Returns the last outcome, or None if the game was already
+/// over.
+pub fn auto_play<T: Number>(
+    host: &mut dyn Host<T>,
+    solver: &mut dyn Solver<T>,
+    mut on_guess: impl FnMut(&Outcome<T>),
+) -> Option<Outcome<T>> {
+    let mut last = None;
+
+    while !host.is_over() {
+        let guess = solver.next_guess();
+        let outcome = host.submit_guess(guess);
+        solver.feedback(guess, outcome.ordering);
+        on_guess(&outcome);
+        last = Some(outcome);
+    }
+
+    last
+}
@@ -0,0 +1,75 @@
+use std::io::{self, BufRead};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::thread;
+
+/// Something that happened on the terminal.
+#[derive(Debug)]
+pub enum Input {
+    /// A line the player typed, without the trailing newline.
+    Line(String),
+    /// Standard input was closed (Ctrl-D, or piped input ran out).
+    Eof,
+    /// The player pressed Ctrl-C.
+    Interrupted,
+    /// Reading standard input failed.
+    Error(io::Error),
+}
+
+/// Reads standard input on a background thread and catches Ctrl-C, so
+/// both arrive at the game loop through one channel.
+///
+/// read_line blocks until the player presses enter, so a Ctrl-C handler
+/// on its own couldn't stop the loop cleanly. Instead the handler and
+/// the reader thread both send an Input into the same channel, and the
+/// game loop waits on that channel with `next`.
+pub struct Terminal {
+    receiver: Receiver<Input>,
+}
+
+impl Terminal {
+    /// Start reading standard input and install the Ctrl-C handler.
+    /// This can only be done once per process, because there is only
+    /// one Ctrl-C handler.
+    pub fn spawn() -> Result<Terminal, ctrlc::Error> {
+        let (sender, receiver) = mpsc::channel();
+
+        let interrupt = sender.clone();
+        ctrlc::set_handler(move || {
+            // The game may already have finished and dropped the
+            // receiver, in which case there is nobody left to tell.
+            let _ = interrupt.send(Input::Interrupted);
+        })?;
+
+        thread::spawn(move || read_lines(sender));
+
+        Ok(Terminal { receiver })
+    }
+
+    /// Wait for the next line, EOF, Ctrl-C or error.
+    pub fn next(&self) -> Input {
+        // The reader thread only stops after sending Eof or Error, so
+        // the channel closing means the same thing as EOF.
+        self.receiver.recv().unwrap_or(Input::Eof)
+    }
+}
+
+fn read_lines(sender: Sender<Input>) {
+    let stdin = io::stdin();
+    loop {
+        let mut line = String::new();
+
+        // read_line returns the number of bytes read. Zero bytes means
+        // we hit the end of the input; without checking for it, the
+        // game would spin forever re-parsing an empty string.
+        let input = match stdin.lock().read_line(&mut line) {
+            Ok(0) => Input::Eof,
+            Ok(_) => Input::Line(line.trim_end_matches(['\r', '\n']).to_string()),
+            Err(err) => Input::Error(err),
+        };
+
+        let done = !matches!(input, Input::Line(_));
+        if sender.send(input).is_err() || done {
+            return;
+        }
+    }
+}
@@ -0,0 +1,419 @@
+use crate::ending::Ending;
+use crate::game::{range_size, seeded_rng, Game};
+use crate::protocol::{self, Setup};
+use crate::secret::Distribution;
+use crate::solver::{self, BinarySearch, GoldenSection, LinearSearch, RandomSearch, Solver};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::io::{self, BufRead, BufReader, Read};
+use std::process::{Child, ChildStdin, Command, Stdio};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// The strategies built into the tournament, by name.
+pub const BUILTIN: [&str; 4] = ["binary", "random", "linear", "golden"];
+
+/// The most rows a histogram gets. Wider spreads of attempts are put
+/// into buckets.
+const HISTOGRAM_ROWS: usize = 20;
+
+/// How wide the longest bar of a histogram is.
+const HISTOGRAM_WIDTH: usize = 40;
+
+/// The most guesses a tournament game allows by default, however big
+/// the range. Linear search over all of -d insane would otherwise take
+/// billions of guesses a game.
+pub const MAX_ATTEMPTS: usize = 10_000;
+
+/// How long a strategy program gets for one game by default, from the
+/// `hello` to its last guess.
+pub const DEFAULT_TIME_LIMIT: Duration = Duration::from_secs(10);
+
+/// How many lines that aren't guesses a strategy program can send in
+/// one game before it is counted as failing.
+pub const MAX_ERRORS: usize = 100;
+
+/// How long a strategy program gets to exit once its input is closed,
+/// before it is killed.
+const EXIT_GRACE: Duration = Duration::from_secs(1);
+
+/// A contestant in the tournament. The runner hands it one game at a
+/// time and counts the guesses it took, so a new strategy only has to
+/// implement this (or be a `Solver`, wrapped in a `SolverStrategy`).
+pub trait Strategy {
+    /// The name the strategy is listed under in the results.
+    fn name(&self) -> &str;
+
+    /// Play `game` until it is won or lost. `rng` is the strategy's own
+    /// random number generator, seeded from the tournament's seed, so
+    /// strategies that guess at random can be replayed too.
+    fn play(&mut self, game: &mut Game, rng: &mut StdRng) -> Result<(), String>;
+}
+
+/// Makes a solver for a game between low and high.
+type MakeSolver = Box<dyn Fn(u32, u32, &mut StdRng) -> Box<dyn Solver>>;
+
+/// A strategy that plays every game with a fresh `Solver`.
+pub struct SolverStrategy {
+    name: String,
+    make: MakeSolver,
+}
+
+impl SolverStrategy {
+    pub fn new(
+        name: &str,
+        make: impl Fn(u32, u32, &mut StdRng) -> Box<dyn Solver> + 'static,
+    ) -> SolverStrategy {
+        SolverStrategy {
+            name: name.to_string(),
+            make: Box::new(make),
+        }
+    }
+
+    /// One of the BUILTIN strategies.
+    pub fn builtin(name: &str) -> Option<SolverStrategy> {
+        let strategy = match name {
+            "binary" => SolverStrategy::new(name, |low, high, _| Box::new(BinarySearch::new(low, high))),
+            "random" => SolverStrategy::new(name, |low, high, rng| {
+                Box::new(RandomSearch::new(low, high, StdRng::seed_from_u64(rng.gen())))
+            }),
+            "linear" => SolverStrategy::new(name, |low, _, _| Box::new(LinearSearch::new(low))),
+            "golden" => SolverStrategy::new(name, |low, high, _| Box::new(GoldenSection::new(low, high))),
+            _ => return None,
+        };
+        Some(strategy)
+    }
+}
+
+impl Strategy for SolverStrategy {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn play(&mut self, game: &mut Game, rng: &mut StdRng) -> Result<(), String> {
+        let mut solver = (self.make)(game.low(), game.high(), rng);
+        solver::auto_play(game, solver.as_mut(), |_| {});
+        Ok(())
+    }
+}
+
+/// A strategy in another program, which plays the JSON-lines protocol
+/// of `--protocol jsonl` from the bot's side: it reads a `hello`, sends
+/// `{"guess": n}` lines and reads the answers, until an `end`. The
+/// program is started once and plays every game in turn, so it should
+/// wait for the next `hello` after an `end`, until its input is closed.
+///
+/// A program that takes longer than its time limit over a game, or sends
+/// MAX_ERRORS lines that aren't guesses, fails (and is killed) rather
+/// than holding up the tournament.
+pub struct CommandStrategy {
+    command: String,
+    child: Child,
+    /// None once the tournament is over and the input is closed.
+    to_program: Option<ChildStdin>,
+    from_program: ProgramOutput,
+    setup: Setup,
+    time_limit: Duration,
+}
+
+impl CommandStrategy {
+    /// Start `command`, split into the program and its arguments at
+    /// whitespace (there is no shell). `setup` is sent in every `hello`.
+    pub fn start(command: &str, setup: Setup) -> Result<CommandStrategy, String> {
+        let mut words = command.split_whitespace();
+        let program = words.next().ok_or("the strategy command is empty")?;
+        let mut child = Command::new(program)
+            .args(words)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()
+            .map_err(|err| format!("could not start '{command}': {err}"))?;
+        let to_program = child.stdin.take();
+        let from_program = ProgramOutput::spawn(child.stdout.take().expect("stdout is piped"));
+
+        Ok(CommandStrategy {
+            command: command.to_string(),
+            child,
+            to_program,
+            from_program,
+            setup,
+            time_limit: DEFAULT_TIME_LIMIT,
+        })
+    }
+
+    /// Give the program `time_limit` for each game instead of
+    /// DEFAULT_TIME_LIMIT.
+    pub fn with_time_limit(mut self, time_limit: Duration) -> CommandStrategy {
+        self.time_limit = time_limit;
+        self
+    }
+}
+
+impl Strategy for CommandStrategy {
+    fn name(&self) -> &str {
+        &self.command
+    }
+
+    fn play(&mut self, game: &mut Game, _: &mut StdRng) -> Result<(), String> {
+        let to_program = self.to_program.as_mut().expect("the input is open until the strategy is dropped");
+        self.from_program.deadline = Instant::now() + self.time_limit;
+        let played = protocol::play(game, &self.setup, &mut self.from_program, to_program, Some(MAX_ERRORS));
+
+        let failure = match played {
+            Ok(Ending::Won | Ending::Lost) => return Ok(()),
+            Err(err) => format!("lost touch with '{}': {err}", self.command),
+            Ok(Ending::Error) if self.from_program.timed_out => format!(
+                "'{}' took more than {} seconds over a game",
+                self.command,
+                self.time_limit.as_secs_f64()
+            ),
+            Ok(Ending::Error) => format!(
+                "'{}' sent {MAX_ERRORS} lines that weren't guesses in one game",
+                self.command
+            ),
+            Ok(Ending::Quit) => format!("'{}' stopped in the middle of a game", self.command),
+        };
+        // The program is out of the tournament, and may be stuck.
+        let _ = self.child.kill();
+        Err(failure)
+    }
+}
+
+impl Drop for CommandStrategy {
+    /// Close the program's input so it knows the tournament is over,
+    /// and wait for it to finish, killing it if it doesn't.
+    fn drop(&mut self) {
+        drop(self.to_program.take());
+        let deadline = Instant::now() + EXIT_GRACE;
+        while let Ok(None) = self.child.try_wait() {
+            if Instant::now() >= deadline {
+                let _ = self.child.kill();
+                break;
+            }
+            thread::sleep(Duration::from_millis(10));
+        }
+        let _ = self.child.wait();
+    }
+}
+
+/// A strategy program's output, read line by line on a thread of its
+/// own so that waiting for the next line can give up at `deadline`.
+/// Reads past the deadline fail with `TimedOut` and set `timed_out`.
+struct ProgramOutput {
+    lines: Receiver<Vec<u8>>,
+    line: Vec<u8>,
+    /// How much of `line` has been read.
+    position: usize,
+    deadline: Instant,
+    timed_out: bool,
+}
+
+impl ProgramOutput {
+    fn spawn(output: impl Read + Send + 'static) -> ProgramOutput {
+        let (sender, lines) = mpsc::channel();
+        thread::spawn(move || {
+            let mut output = BufReader::new(output);
+            loop {
+                let mut line = Vec::new();
+                match output.read_until(b'\n', &mut line) {
+                    Ok(0) | Err(_) => break,
+                    Ok(_) if sender.send(line).is_err() => break,
+                    Ok(_) => {}
+                }
+            }
+        });
+        ProgramOutput {
+            lines,
+            line: Vec::new(),
+            position: 0,
+            deadline: Instant::now(),
+            timed_out: false,
+        }
+    }
+}
+
+impl Read for ProgramOutput {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let available = self.fill_buf()?;
+        let read = available.len().min(buf.len());
+        buf[..read].copy_from_slice(&available[..read]);
+        self.consume(read);
+        Ok(read)
+    }
+}
+
+impl BufRead for ProgramOutput {
+    fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        if self.position == self.line.len() {
+            let wait = self.deadline.saturating_duration_since(Instant::now());
+            match self.lines.recv_timeout(wait) {
+                Ok(line) => {
+                    self.line = line;
+                    self.position = 0;
+                }
+                Err(RecvTimeoutError::Timeout) => {
+                    self.timed_out = true;
+                    return Err(io::Error::new(io::ErrorKind::TimedOut, "the time limit is up"));
+                }
+                // The program closed its output: the end of the input.
+                Err(RecvTimeoutError::Disconnected) => {}
+            }
+        }
+        Ok(&self.line[self.position..])
+    }
+
+    fn consume(&mut self, amount: usize) {
+        self.position += amount;
+    }
+}
+
+/// What the tournament plays.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Settings {
+    pub low: u32,
+    pub high: u32,
+    pub max_attempts: Option<usize>,
+    /// How the secrets are drawn.
+    pub secret: Distribution,
+    /// How many games every strategy plays.
+    pub games: usize,
+    /// Seed for the secrets and the strategies' random number
+    /// generators, so a tournament can be run again exactly.
+    pub seed: Option<u64>,
+}
+
+/// How one strategy did.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Results {
+    pub name: String,
+    /// The guesses taken in each game, in the order the games were
+    /// played.
+    pub attempts: Vec<usize>,
+    /// How many games were won.
+    pub wins: usize,
+}
+
+impl Results {
+    pub fn games(&self) -> usize {
+        self.attempts.len()
+    }
+
+    pub fn mean(&self) -> f64 {
+        self.attempts.iter().sum::<usize>() as f64 / self.games().max(1) as f64
+    }
+
+    /// The middle number of guesses, or the mean of the two middle ones
+    /// for an even number of games.
+    pub fn median(&self) -> f64 {
+        let mut sorted = self.attempts.clone();
+        sorted.sort_unstable();
+        match sorted.len() {
+            0 => 0.0,
+            len if len % 2 == 1 => sorted[len / 2] as f64,
+            len => (sorted[len / 2 - 1] + sorted[len / 2]) as f64 / 2.0,
+        }
+    }
+
+    pub fn worst(&self) -> usize {
+        self.attempts.iter().copied().max().unwrap_or(0)
+    }
+
+    /// How many games took each number of guesses, as rows of (fewest,
+    /// most, games). With more than HISTOGRAM_ROWS different numbers of
+    /// guesses, each row covers a bucket of them.
+    pub fn histogram(&self) -> Vec<(usize, usize, usize)> {
+        let Some(&fewest) = self.attempts.iter().min() else {
+            return Vec::new();
+        };
+        let most = self.worst();
+        let bucket = (most - fewest + 1).div_ceil(HISTOGRAM_ROWS);
+        let mut rows: Vec<(usize, usize, usize)> = (fewest..=most)
+            .step_by(bucket)
+            .map(|start| (start, (start + bucket - 1).min(most), 0))
+            .collect();
+        for &attempts in &self.attempts {
+            rows[(attempts - fewest) / bucket].2 += 1;
+        }
+        rows
+    }
+}
+
+/// How many guesses a tournament game allows by default: as many as
+/// there are numbers, so even linear search can finish, up to
+/// MAX_ATTEMPTS.
+pub fn default_max_attempts(low: u32, high: u32) -> usize {
+    usize::try_from(range_size(low, high)).map_or(MAX_ATTEMPTS, |size| size.min(MAX_ATTEMPTS))
+}
+
+/// Play `settings.games` games with every strategy and collect how they
+/// did. Every strategy gets the same secrets, drawn up front, so the
+/// results can be compared game by game. Stops at the first strategy
+/// that fails (a program that crashes, say).
+pub fn run(strategies: &mut [Box<dyn Strategy>], settings: &Settings) -> Result<Vec<Results>, String> {
+    let source = settings.secret.source(settings.low, settings.high)?;
+    let mut rng = seeded_rng(settings.seed);
+    let secrets: Vec<u32> = (0..settings.games).map(|_| source.draw(&mut rng)).collect();
+
+    let mut all = Vec::new();
+    for strategy in strategies.iter_mut() {
+        let mut strategy_rng = StdRng::seed_from_u64(rng.gen());
+        let mut results = Results {
+            name: strategy.name().to_string(),
+            attempts: Vec::with_capacity(secrets.len()),
+            wins: 0,
+        };
+        for &secret in &secrets {
+            let mut game = Game::with_secret(settings.low, settings.high, secret)
+                .with_max_attempts(settings.max_attempts);
+            strategy
+                .play(&mut game, &mut strategy_rng)
+                .map_err(|err| format!("{}: {err}", strategy.name()))?;
+            results.attempts.push(game.attempts());
+            if game.is_won() {
+                results.wins += 1;
+            }
+        }
+        all.push(results);
+    }
+    Ok(all)
+}
+
+/// A table of every strategy's wins and mean, median and worst number
+/// of guesses, followed by a histogram of the guesses for each.
+pub fn render(results: &[Results]) -> String {
+    let width = results
+        .iter()
+        .map(|results| results.name.chars().count())
+        .max()
+        .unwrap_or(0)
+        .max("strategy".len());
+
+    let mut out = format!("{:<width$}  {:>11}  {:>7}  {:>7}  {:>6}\n", "strategy", "won", "mean", "median", "worst");
+    for results in results {
+        out.push_str(&format!(
+            "{:<width$}  {:>11}  {:>7.2}  {:>7.1}  {:>6}\n",
+            results.name,
+            format!("{}/{}", results.wins, results.games()),
+            results.mean(),
+            results.median(),
+            results.worst()
+        ));
+    }
+
+    for results in results {
+        out.push_str(&format!("\n{}: games by number of guesses\n", results.name));
+        let rows = results.histogram();
+        let tallest = rows.iter().map(|&(_, _, games)| games).max().unwrap_or(0).max(1);
+        for (fewest, most, games) in rows {
+            let label = if fewest == most {
+                fewest.to_string()
+            } else {
+                format!("{fewest}-{most}")
+            };
+            let bar = "#".repeat((games * HISTOGRAM_WIDTH).div_ceil(tallest));
+            out.push_str(&format!("{label:>9} | {bar:<HISTOGRAM_WIDTH$} {games}\n"));
+        }
+    }
+    out
+}
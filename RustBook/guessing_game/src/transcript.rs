@@ -0,0 +1,195 @@
+use crate::config::{Config, Difficulty, Mode};
+use crate::ending::Ending;
+use crate::feedback::{self, FeedbackMode};
+use crate::game::{seeded_rng, Game};
+use crate::leaderboard;
+use crate::secret::Distribution;
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+/// The transcript format. Replaying a transcript with another version
+/// is refused rather than guessed at.
+pub const VERSION: u32 = 1;
+
+/// How a guess compared with the secret, written out in words so the
+/// JSON is readable (and usable from other languages).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Comparison {
+    /// The guess was too small.
+    Less,
+    /// The guess was too big.
+    Greater,
+    Equal,
+}
+
+impl From<Ordering> for Comparison {
+    fn from(ordering: Ordering) -> Comparison {
+        match ordering {
+            Ordering::Less => Comparison::Less,
+            Ordering::Greater => Comparison::Greater,
+            Ordering::Equal => Comparison::Equal,
+        }
+    }
+}
+
+/// One guess in a transcript.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Step {
+    /// When the guess was made, in seconds from the start.
+    pub seconds: f64,
+    pub guess: u32,
+    pub result: Comparison,
+    /// The line the game printed for it, such as "Too small!" or
+    /// "Warm. Warmer than your last guess."
+    pub feedback: String,
+    pub remaining: Option<usize>,
+}
+
+/// A record of one game: how it was set up, every guess with its time
+/// and answer, and how it ended. Written as JSON with --record, and
+/// played back with `guessing_game replay`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Transcript {
+    pub version: u32,
+    pub player: String,
+    /// True if the computer played (--auto).
+    pub auto: bool,
+    /// The day the game was played, as YYYY-MM-DD (UTC).
+    pub date: String,
+    pub low: u32,
+    pub high: u32,
+    /// The preset the range came from, or None for a custom range.
+    pub difficulty: Option<Difficulty>,
+    /// How the secret was drawn, as given to --secret.
+    pub distribution: String,
+    /// The seed the secret was drawn with, if the game was seeded.
+    /// With it, `verify` can draw the secret again and check every
+    /// answer.
+    pub seed: Option<u64>,
+    pub max_attempts: Option<usize>,
+    pub feedback: FeedbackMode,
+    pub guesses: Vec<Step>,
+    pub ending: Ending,
+    /// The secret, or None if the game was saved to be carried on
+    /// later and the secret is still a secret.
+    pub secret: Option<u32>,
+    pub seconds: f64,
+}
+
+/// What `Transcript::verify` could check the answers against.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Check {
+    /// The secret drawn again from the recorded seed.
+    Seed(u64),
+    /// The recorded secret, for a game that wasn't seeded.
+    Secret,
+    /// Nothing: the game wasn't seeded and the secret wasn't recorded.
+    Unchecked,
+}
+
+impl Transcript {
+    /// The transcript of `game`, which was set up from `config` and has
+    /// ended. `hide_secret` leaves the secret out, for games that were
+    /// saved rather than finished.
+    pub fn record(config: &Config, game: &Game, ending: Ending, hide_secret: bool) -> Transcript {
+        // The answers are worked out again on a fresh copy of the game,
+        // so each one is described as it was when the guess was made.
+        let mut replay = Game::with_secret(game.low(), game.high(), game.secret())
+            .with_max_attempts(game.max_attempts());
+        let guesses = game
+            .history()
+            .iter()
+            .zip(game.guess_times())
+            .map(|(&guess, time)| {
+                let outcome = replay.submit_guess(guess);
+                Step {
+                    seconds: time.as_secs_f64(),
+                    guess,
+                    result: outcome.ordering.into(),
+                    feedback: feedback::describe(config.feedback, &replay, &outcome),
+                    remaining: outcome.remaining,
+                }
+            })
+            .collect();
+
+        Transcript {
+            version: VERSION,
+            player: config.name.clone(),
+            auto: config.mode == Mode::Auto,
+            date: leaderboard::today(),
+            low: game.low(),
+            high: game.high(),
+            difficulty: config.difficulty,
+            distribution: config.secret.name(),
+            seed: config.seed,
+            max_attempts: game.max_attempts(),
+            feedback: config.feedback,
+            guesses,
+            ending,
+            secret: if hide_secret { None } else { Some(game.secret()) },
+            seconds: game.elapsed().as_secs_f64(),
+        }
+    }
+
+    /// Write the transcript to `path`, atomically like the leaderboard.
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        leaderboard::write_json(path, self)
+    }
+
+    pub fn load(path: &Path) -> io::Result<Transcript> {
+        let contents = fs::read_to_string(path)?;
+        let transcript: Transcript = serde_json::from_str(&contents)?;
+        if transcript.version != VERSION {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!(
+                    "the transcript is version {}, but this game reads version {VERSION}",
+                    transcript.version
+                ),
+            ));
+        }
+        Ok(transcript)
+    }
+
+    /// Play the game again and check that every guess gets the answer
+    /// the transcript says it got. A seeded game draws its secret again
+    /// from the seed, exactly as the game did, so a transcript can't
+    /// claim answers the seed doesn't give. Otherwise the answers are
+    /// checked against the recorded secret, if there is one.
+    pub fn verify(&self) -> Result<Check, String> {
+        let (mut game, check) = match (self.seed, self.secret) {
+            (Some(seed), _) => {
+                let source = Distribution::from_name(&self.distribution)?.source(self.low, self.high)?;
+                let game = Game::from_source(source.as_ref(), &mut seeded_rng(Some(seed)));
+                if self.secret.is_some_and(|secret| secret != game.secret()) {
+                    return Err(format!(
+                        "seed {seed} draws {}, not the recorded secret",
+                        game.secret()
+                    ));
+                }
+                (game, Check::Seed(seed))
+            }
+            (None, Some(secret)) => (Game::with_secret(self.low, self.high, secret), Check::Secret),
+            (None, None) => return Ok(Check::Unchecked),
+        };
+        game = game.with_max_attempts(self.max_attempts);
+
+        for (index, step) in self.guesses.iter().enumerate() {
+            let outcome = game.submit_guess(step.guess);
+            let feedback = feedback::describe(self.feedback, &game, &outcome);
+            if Comparison::from(outcome.ordering) != step.result || feedback != step.feedback {
+                return Err(format!(
+                    "guess #{} ({}) was answered \"{}\", but the game answers \"{feedback}\"",
+                    index + 1,
+                    step.guess,
+                    step.feedback
+                ));
+            }
+        }
+        Ok(check)
+    }
+}
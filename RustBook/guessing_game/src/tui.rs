@@ -0,0 +1,209 @@
+use crate::ending::Ending;
+use crate::game::Game;
+use crate::input::{parse_guess, Command};
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEventKind, KeyModifiers};
+use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::style::{Color, Style, Stylize};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, List, ListItem, Paragraph};
+use ratatui::{DefaultTerminal, Frame};
+use std::io;
+use std::time::Duration;
+
+/// How often the screen is redrawn while waiting for a key, so the timer
+/// keeps ticking.
+const TICK: Duration = Duration::from_millis(100);
+
+/// What the player is typing and the last thing the game said.
+struct Screen<'a> {
+    title: &'a str,
+    input: String,
+    message: String,
+}
+
+/// Play a game in a full-screen terminal UI: a number line that shrinks
+/// to the numbers still possible after each "Too small!"/"Too big!", a
+/// history of guesses, the attempts remaining and a timer.
+///
+/// The terminal is put back the way it was before returning, even if
+/// drawing fails, so the caller can print the usual summary.
+pub fn play(game: &mut Game, title: &str) -> io::Result<Ending> {
+    let mut terminal = ratatui::init();
+    let result = run(&mut terminal, game, title);
+    ratatui::restore();
+    result
+}
+
+fn run(terminal: &mut DefaultTerminal, game: &mut Game, title: &str) -> io::Result<Ending> {
+    let mut screen = Screen {
+        title,
+        input: String::new(),
+        message: String::from("Type a guess and press enter. Esc or \"quit\" gives up."),
+    };
+
+    let ending = loop {
+        terminal.draw(|frame| draw(frame, game, &screen))?;
+
+        if !event::poll(TICK)? {
+            continue;
+        }
+        let Event::Key(key) = event::read()? else {
+            continue;
+        };
+        if key.kind != KeyEventKind::Press {
+            continue;
+        }
+
+        match key.code {
+            // Raw mode turns CTRL + C into an ordinary key press, so it
+            // is handled here rather than by a signal handler.
+            KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                break Ending::Quit;
+            }
+            KeyCode::Esc => break Ending::Quit,
+            KeyCode::Backspace => {
+                screen.input.pop();
+            }
+            KeyCode::Char(c) => screen.input.push(c),
+            KeyCode::Enter => {
+                let line = std::mem::take(&mut screen.input);
+                if let Some(Command::Quit) = Command::parse(&line) {
+                    break Ending::Quit;
+                }
+
+                // Invalid input is explained and doesn't use an attempt,
+                // just like in line mode.
+                let guess = match parse_guess(&line, game.low(), game.high()) {
+                    Ok(guess) => guess,
+                    Err(err) => {
+                        screen.message = err.to_string();
+                        continue;
+                    }
+                };
+
+                let outcome = game.submit_guess(guess);
+                screen.message = format!("You guessed {guess}: {}", outcome.message());
+                if outcome.is_win() {
+                    break Ending::Won;
+                }
+                if outcome.is_loss() {
+                    screen.message = format!("Out of attempts! The secret number was {}.", game.secret());
+                    break Ending::Lost;
+                }
+            }
+            _ => {}
+        }
+    };
+
+    // Leave the final board on screen until the player has seen it.
+    screen.message.push_str("  Press any key to exit.");
+    terminal.draw(|frame| draw(frame, game, &screen))?;
+    loop {
+        if let Event::Key(key) = event::read()? {
+            if key.kind == KeyEventKind::Press {
+                break;
+            }
+        }
+    }
+
+    Ok(ending)
+}
+
+fn draw(frame: &mut Frame, game: &Game, screen: &Screen) {
+    let [title_area, line_area, status_area, history_area, message_area, input_area] =
+        Layout::vertical([
+            Constraint::Length(1),
+            Constraint::Length(4),
+            Constraint::Length(1),
+            Constraint::Min(3),
+            Constraint::Length(1),
+            Constraint::Length(3),
+        ])
+        .areas(frame.area());
+
+    frame.render_widget(Line::from(screen.title).bold().centered(), title_area);
+    draw_number_line(frame, game, line_area);
+
+    let attempts = match game.remaining() {
+        Some(remaining) => format!("Attempts left: {remaining}"),
+        None => format!("Attempts: {}", game.attempts()),
+    };
+    let elapsed = game.elapsed().as_secs_f64();
+    let status = format!(
+        "{attempts}    Time: {:02}:{:04.1}",
+        (elapsed / 60.0) as u64,
+        elapsed % 60.0
+    );
+    frame.render_widget(Line::from(status), status_area);
+
+    // Newest guesses at the top.
+    let history: Vec<ListItem> = game.history()
+        .iter()
+        .enumerate()
+        .rev()
+        .map(|(index, &guess)| {
+            let (text, color) = match guess.cmp(&game.secret()) {
+                std::cmp::Ordering::Less => ("Too small!", Color::Blue),
+                std::cmp::Ordering::Greater => ("Too big!", Color::Red),
+                std::cmp::Ordering::Equal => ("You win!", Color::Green),
+            };
+            ListItem::new(Line::from(vec![
+                Span::raw(format!("#{:<3} {guess:>10}  ", index + 1)),
+                Span::styled(text, Style::default().fg(color)),
+            ]))
+        })
+        .collect();
+    frame.render_widget(
+        List::new(history).block(Block::bordered().title(" History ")),
+        history_area,
+    );
+
+    frame.render_widget(Line::from(screen.message.as_str()).italic(), message_area);
+    frame.render_widget(
+        Paragraph::new(format!("> {}_", screen.input))
+            .block(Block::bordered().title(" Your guess ")),
+        input_area,
+    );
+}
+
+/// The whole range as a bar, with the part the secret could still be in
+/// highlighted.
+fn draw_number_line(frame: &mut Frame, game: &Game, area: Rect) {
+    let block = Block::bordered().title(" Number line ");
+    let width = block.inner(area).width.max(1) as u64;
+
+    let low = u64::from(game.low());
+    let size = game.range_size();
+    let (possible_low, possible_high) = game.possible_range();
+
+    // Each column covers size / width numbers (at least one). A column
+    // is lit if any of its numbers are still possible.
+    let bar: String = (0..width)
+        .map(|column| {
+            let first = low + column * size / width;
+            let last = (low + (column + 1) * size / width).max(first + 1) - 1;
+            if last >= u64::from(possible_low) && first <= u64::from(possible_high) {
+                '█'
+            } else {
+                '─'
+            }
+        })
+        .collect();
+
+    let labels = format!(
+        "{}  ..  still possible: {}-{}  ..  {}",
+        game.low(),
+        possible_low,
+        possible_high,
+        game.high()
+    );
+
+    frame.render_widget(
+        Paragraph::new(vec![
+            Line::from(bar).fg(Color::Green),
+            Line::from(labels).centered(),
+        ])
+        .block(block),
+        area,
+    );
+}
@@ -0,0 +1,350 @@
+use rand::{Rng, RngCore};
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+/// The word list that comes with the game: common five-letter English
+/// words.
+const BUNDLED_WORDS: &str = include_str!("words.txt");
+
+/// How many guesses a word game allows unless --attempts says
+/// otherwise, as in Wordle.
+pub const DEFAULT_ATTEMPTS: usize = 6;
+
+/// The words the secret is drawn from, which are also the only words
+/// accepted as guesses. Every word has the same number of letters.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WordList {
+    words: Vec<String>,
+    length: usize,
+}
+
+impl WordList {
+    /// The bundled English list.
+    pub fn bundled() -> WordList {
+        WordList::parse(BUNDLED_WORDS).expect("the bundled word list is valid")
+    }
+
+    /// Load a list from a file, for playing in another language. See
+    /// `parse` for the format.
+    pub fn load(path: &Path) -> io::Result<WordList> {
+        let text = fs::read_to_string(path)?;
+        WordList::parse(&text).map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))
+    }
+
+    /// Read a list with one word per line. Blank lines and lines
+    /// starting with # are skipped, words are lowercased, and any
+    /// letters count (so "straße" or "niño" are fine), but every word
+    /// must have as many letters as the first.
+    pub fn parse(text: &str) -> Result<WordList, String> {
+        let mut words: Vec<String> = Vec::new();
+        let mut length = 0;
+
+        for (number, line) in text.lines().enumerate() {
+            let word = line.trim().to_lowercase();
+            if word.is_empty() || word.starts_with('#') {
+                continue;
+            }
+            if !word.chars().all(char::is_alphabetic) {
+                return Err(format!("line {}: '{word}' is not a word", number + 1));
+            }
+
+            let letters = word.chars().count();
+            if words.is_empty() {
+                length = letters;
+            } else if letters != length {
+                return Err(format!(
+                    "line {}: '{word}' has {letters} letters, but the words before it have {length}",
+                    number + 1
+                ));
+            }
+            words.push(word);
+        }
+
+        if words.is_empty() {
+            return Err(String::from("the word list is empty"));
+        }
+        words.sort();
+        words.dedup();
+        Ok(WordList { words, length })
+    }
+
+    /// How many letters every word has.
+    pub fn length(&self) -> usize {
+        self.length
+    }
+
+    pub fn len(&self) -> usize {
+        self.words.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.words.is_empty()
+    }
+
+    pub fn contains(&self, word: &str) -> bool {
+        self.words.binary_search_by(|probe| probe.as_str().cmp(word)).is_ok()
+    }
+
+    /// Draw a secret word.
+    pub fn random_word(&self, rng: &mut dyn RngCore) -> String {
+        self.words[rng.gen_range(0..self.words.len())].clone()
+    }
+}
+
+/// The feedback for one letter of a guess.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Mark {
+    /// The right letter in the right place.
+    Green,
+    /// A letter in the word, but somewhere else.
+    Yellow,
+    /// A letter that isn't in the word (or not as many times as it
+    /// was guessed).
+    Gray,
+}
+
+impl Mark {
+    pub fn square(&self) -> char {
+        match self {
+            Mark::Green => '🟩',
+            Mark::Yellow => '🟨',
+            Mark::Gray => '⬜',
+        }
+    }
+}
+
+/// Mark every letter of `guess` against `secret`. As in Wordle, a
+/// letter is only yellow as many times as the secret has it to spare
+/// after the greens, so guessing "speed" for "abide" gives one yellow
+/// e, not two.
+pub fn mark(secret: &str, guess: &str) -> Vec<Mark> {
+    let secret: Vec<char> = secret.chars().collect();
+    let guess: Vec<char> = guess.chars().collect();
+    let mut marks = vec![Mark::Gray; guess.len()];
+
+    // Greens first, keeping the secret's other letters to hand out as
+    // yellows from left to right.
+    let mut spare: Vec<char> = Vec::new();
+    for (index, (&wanted, &guessed)) in secret.iter().zip(&guess).enumerate() {
+        if wanted == guessed {
+            marks[index] = Mark::Green;
+        } else {
+            spare.push(wanted);
+        }
+    }
+    for (index, &guessed) in guess.iter().enumerate() {
+        if marks[index] == Mark::Green {
+            continue;
+        }
+        if let Some(position) = spare.iter().position(|&letter| letter == guessed) {
+            spare.swap_remove(position);
+            marks[index] = Mark::Yellow;
+        }
+    }
+
+    marks
+}
+
+/// A guess and its marks as two lines, the letters spaced out above
+/// their squares. Letters whose capital is more than one letter (ß is
+/// SS) stay lowercase so they still line up.
+pub fn render(guess: &str, marks: &[Mark]) -> String {
+    let capital = |c: char| {
+        let mut upper = c.to_uppercase();
+        match (upper.next(), upper.next()) {
+            (Some(upper), None) => upper,
+            _ => c,
+        }
+    };
+    let letters: Vec<String> = guess.chars().map(|c| capital(c).to_string()).collect();
+    let squares: String = marks.iter().map(Mark::square).collect();
+    format!("{}\n{squares}", letters.join(" "))
+}
+
+/// Why a line typed at the prompt isn't a usable word. None of these
+/// use up an attempt.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WordError {
+    /// Nothing (or only whitespace) was typed.
+    Empty,
+    /// The input contains something other than letters.
+    NotAWord(String),
+    /// The word has the wrong number of letters.
+    WrongLength { found: usize, expected: usize },
+    /// The word isn't in the word list.
+    Unknown(String),
+}
+
+impl fmt::Display for WordError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            WordError::Empty => write!(f, "Please type a word before pressing enter."),
+            WordError::NotAWord(input) => {
+                write!(f, "'{input}' is not a word. Please use only letters.")
+            }
+            WordError::WrongLength { found, expected } => {
+                write!(f, "That word has {found} letters. The secret word has {expected}.")
+            }
+            WordError::Unknown(word) => write!(f, "'{word}' is not in the word list."),
+        }
+    }
+}
+
+impl Error for WordError {}
+
+/// Turn a line typed at the prompt into a guess from `words`.
+/// Surrounding whitespace is ignored, and case doesn't matter.
+pub fn parse_word(input: &str, words: &WordList) -> Result<String, WordError> {
+    let word = input.trim().to_lowercase();
+    if word.is_empty() {
+        return Err(WordError::Empty);
+    }
+    if !word.chars().all(char::is_alphabetic) {
+        return Err(WordError::NotAWord(input.trim().to_string()));
+    }
+
+    let found = word.chars().count();
+    if found != words.length() {
+        return Err(WordError::WrongLength {
+            found,
+            expected: words.length(),
+        });
+    }
+    if !words.contains(&word) {
+        return Err(WordError::Unknown(word));
+    }
+    Ok(word)
+}
+
+/// A round of the word game: the same shape as Game, with a word for a
+/// secret and a row of marks instead of an Ordering for an answer.
+#[derive(Debug, Clone)]
+pub struct WordGame {
+    secret: String,
+    history: Vec<(String, Vec<Mark>)>,
+    max_attempts: Option<usize>,
+}
+
+/// The result of submitting one guess.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WordOutcome {
+    pub guess: String,
+    pub marks: Vec<Mark>,
+    pub attempts: usize,
+    pub remaining: Option<usize>,
+}
+
+impl WordOutcome {
+    pub fn is_win(&self) -> bool {
+        self.marks.iter().all(|&mark| mark == Mark::Green)
+    }
+
+    /// True if this guess was wrong and it was the last one allowed.
+    pub fn is_loss(&self) -> bool {
+        !self.is_win() && self.remaining == Some(0)
+    }
+}
+
+impl WordGame {
+    pub fn new(words: &WordList, rng: &mut dyn RngCore) -> WordGame {
+        WordGame::with_secret(words.random_word(rng))
+    }
+
+    pub fn with_secret(secret: String) -> WordGame {
+        WordGame {
+            secret,
+            history: Vec::new(),
+            max_attempts: None,
+        }
+    }
+
+    /// Limit the number of guesses. None means the player can keep
+    /// guessing forever.
+    pub fn with_max_attempts(mut self, max_attempts: Option<usize>) -> WordGame {
+        self.max_attempts = max_attempts;
+        self
+    }
+
+    pub fn submit_guess(&mut self, guess: String) -> WordOutcome {
+        let marks = mark(&self.secret, &guess);
+        self.history.push((guess.clone(), marks.clone()));
+        WordOutcome {
+            guess,
+            marks,
+            attempts: self.history.len(),
+            remaining: self.remaining(),
+        }
+    }
+
+    pub fn secret(&self) -> &str {
+        &self.secret
+    }
+
+    pub fn history(&self) -> &[(String, Vec<Mark>)] {
+        &self.history
+    }
+
+    pub fn attempts(&self) -> usize {
+        self.history.len()
+    }
+
+    pub fn remaining(&self) -> Option<usize> {
+        self.max_attempts
+            .map(|max| max.saturating_sub(self.attempts()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The marks for `guess` as G, Y and . for green, yellow and gray.
+    fn marks(secret: &str, guess: &str) -> String {
+        mark(secret, guess)
+            .iter()
+            .map(|mark| match mark {
+                Mark::Green => 'G',
+                Mark::Yellow => 'Y',
+                Mark::Gray => '.',
+            })
+            .collect()
+    }
+
+    #[test]
+    fn letters_are_marked_by_place() {
+        assert_eq!(marks("crane", "crane"), "GGGGG");
+        assert_eq!(marks("crane", "nacre"), "YYYYG");
+        assert_eq!(marks("crane", "fluff"), ".....");
+    }
+
+    #[test]
+    fn a_letter_guessed_twice_is_yellow_once() {
+        // The secret has one e, so only the first e of "speed" counts.
+        assert_eq!(marks("abide", "speed"), "..Y.Y");
+    }
+
+    #[test]
+    fn a_green_uses_up_the_letter_before_any_yellow() {
+        // The e in "those" is matched by the last e of "geese", so the
+        // earlier ones are gray even though they come first.
+        assert_eq!(marks("those", "geese"), "...GG");
+    }
+
+    #[test]
+    fn a_letter_the_secret_has_twice_can_be_yellow_twice() {
+        assert_eq!(marks("geese", "eagle"), "Y.Y.G");
+        assert_eq!(marks("geese", "ember"), "Y..Y.");
+        assert_eq!(marks("geese", "other"), "...Y.");
+    }
+
+    #[test]
+    fn repeats_on_both_sides() {
+        assert_eq!(marks("speed", "eerie"), "YY...");
+        assert_eq!(marks("ebbed", "eeeee"), "G..G.");
+        assert_eq!(marks("llama", "allay"), "YGYY.");
+        assert_eq!(marks("eerie", "speed"), "..YY.");
+    }
+}
@@ -0,0 +1,440 @@
+// Integration tests for the HTTP API. Each test starts a server on a
+// free port on localhost and drives it with a small HTTP client built on
+// TcpStream, the same way any other program would.
+
+use guessing_game::http::{self, ApiSettings};
+use guessing_game::secret::Distribution;
+use serde_json::{json, Value};
+use std::io::{BufRead, BufReader, ErrorKind, Read, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::thread;
+use std::time::Duration;
+
+/// Settings for games between 1 and 100.
+fn settings() -> ApiSettings {
+    ApiSettings {
+        low: 1,
+        high: 100,
+        difficulty: None,
+        max_attempts: Some(7),
+        secret: Distribution::Uniform,
+        seed: Some(42),
+        session_timeout: Duration::from_secs(60),
+        max_connections: http::DEFAULT_MAX_CONNECTIONS,
+        max_games: http::DEFAULT_MAX_GAMES,
+    }
+}
+
+/// Start a server and return its address.
+fn start(settings: ApiSettings) -> SocketAddr {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let address = listener.local_addr().unwrap();
+    thread::spawn(move || http::serve(listener, settings));
+    address
+}
+
+fn server() -> SocketAddr {
+    start(settings())
+}
+
+/// A response: the status, the headers (names in lowercase) and the
+/// body as JSON.
+struct Response {
+    status: u16,
+    headers: Vec<(String, String)>,
+    body: Value,
+}
+
+impl Response {
+    fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(header, _)| header == name)
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+/// Send one request on `stream` and read the response to it.
+fn exchange(stream: &mut TcpStream, method: &str, path: &str, body: &str, close: bool) -> Response {
+    let connection = if close { "close" } else { "keep-alive" };
+    write!(
+        stream,
+        "{method} {path} HTTP/1.1\r\nHost: localhost\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: {connection}\r\n\r\n{body}",
+        body.len()
+    )
+    .unwrap();
+
+    let mut reader = BufReader::new(stream);
+    let mut status_line = String::new();
+    reader.read_line(&mut status_line).unwrap();
+    let status = status_line.split_whitespace().nth(1).unwrap().parse().unwrap();
+
+    let mut headers = Vec::new();
+    loop {
+        let mut line = String::new();
+        reader.read_line(&mut line).unwrap();
+        let line = line.trim_end();
+        if line.is_empty() {
+            break;
+        }
+        let (name, value) = line.split_once(':').unwrap();
+        headers.push((name.to_ascii_lowercase(), value.trim().to_string()));
+    }
+
+    let length = headers
+        .iter()
+        .find(|(name, _)| name == "content-length")
+        .map(|(_, value)| value.parse().unwrap())
+        .unwrap();
+    let mut body = vec![0; length];
+    reader.read_exact(&mut body).unwrap();
+
+    Response {
+        status,
+        headers,
+        body: serde_json::from_slice(&body).unwrap(),
+    }
+}
+
+/// Send one request on a connection of its own.
+fn request(address: SocketAddr, method: &str, path: &str, body: &str) -> Response {
+    let mut stream = TcpStream::connect(address).unwrap();
+    exchange(&mut stream, method, path, body, true)
+}
+
+fn create(address: SocketAddr, options: Value) -> String {
+    let response = request(address, "POST", "/games", &options.to_string());
+    assert_eq!(response.status, 201, "{}", response.body);
+    response.body["id"].as_str().unwrap().to_string()
+}
+
+fn guess(address: SocketAddr, id: &str, guess: u32) -> Response {
+    request(address, "POST", &format!("/games/{id}/guesses"), &json!({ "guess": guess }).to_string())
+}
+
+/// Play a game with binary search until it is over, returning the last
+/// reply.
+fn solve(address: SocketAddr, id: &str, mut low: u32, mut high: u32) -> Value {
+    loop {
+        let middle = low + (high - low) / 2;
+        let reply = guess(address, id, middle);
+        assert_eq!(reply.status, 200, "{}", reply.body);
+        match reply.body["result"].as_str().unwrap() {
+            "less" => low = middle + 1,
+            "greater" => high = middle - 1,
+            _ => return reply.body,
+        }
+        if reply.body["status"] != "playing" {
+            return reply.body;
+        }
+    }
+}
+
+#[test]
+fn creating_a_game_uses_the_server_settings() {
+    let address = server();
+    let response = request(address, "POST", "/games", "");
+
+    assert_eq!(response.status, 201);
+    let id = response.body["id"].as_str().unwrap();
+    assert_eq!(response.header("location"), Some(format!("/games/{id}").as_str()));
+    assert_eq!(response.body["low"], 1);
+    assert_eq!(response.body["high"], 100);
+    assert_eq!(response.body["max_attempts"], 7);
+    assert_eq!(response.body["attempts"], 0);
+    assert_eq!(response.body["status"], "playing");
+    assert_eq!(response.body["secret"], Value::Null);
+}
+
+#[test]
+fn options_set_the_range_and_attempts() {
+    let address = server();
+    let easy = request(address, "POST", "/games", r#"{"difficulty": "easy"}"#);
+    assert_eq!(easy.body["high"], 10);
+    assert_eq!(easy.body["difficulty"], "easy");
+    assert_eq!(easy.body["max_attempts"], 4);
+
+    let custom = request(address, "POST", "/games", r#"{"min": 50, "max": 60, "attempts": "unlimited"}"#);
+    assert_eq!(custom.body["low"], 50);
+    assert_eq!(custom.body["high"], 60);
+    assert_eq!(custom.body["difficulty"], Value::Null);
+    assert_eq!(custom.body["max_attempts"], Value::Null);
+}
+
+#[test]
+fn bad_options_are_rejected() {
+    let address = server();
+    for options in [
+        r#"{"min": 10, "max": 5}"#,
+        r#"{"attempts": 0}"#,
+        r#"{"difficulty": "impossible"}"#,
+        r#"{"secret": "primes", "min": 24, "max": 28}"#,
+        r#"{"colour": "blue"}"#,
+    ] {
+        let response = request(address, "POST", "/games", options);
+        assert_eq!(response.status, 400, "{options}");
+        assert_eq!(response.body["code"], "invalid_options", "{options}");
+    }
+
+    let response = request(address, "POST", "/games", "{");
+    assert_eq!(response.status, 400);
+    assert_eq!(response.body["code"], "invalid_json");
+}
+
+#[test]
+fn binary_search_wins_and_the_game_is_over() {
+    let address = server();
+    let id = create(address, json!({}));
+    let last = solve(address, &id, 1, 100);
+
+    assert_eq!(last["result"], "equal");
+    assert_eq!(last["status"], "won");
+    let secret = last["secret"].as_u64().unwrap();
+    assert_eq!(last["guess"].as_u64(), Some(secret));
+
+    let state = request(address, "GET", &format!("/games/{id}"), "");
+    assert_eq!(state.status, 200);
+    assert_eq!(state.body["status"], "won");
+    assert_eq!(state.body["secret"].as_u64(), Some(secret));
+    let guesses = state.body["guesses"].as_array().unwrap();
+    assert_eq!(guesses.len(), state.body["attempts"].as_u64().unwrap() as usize);
+    assert_eq!(guesses.last().unwrap()["result"], "equal");
+
+    let late = guess(address, &id, 50);
+    assert_eq!(late.status, 409);
+    assert_eq!(late.body["code"], "game_over");
+}
+
+#[test]
+fn running_out_of_attempts_loses() {
+    let address = server();
+    let id = create(address, json!({ "secret": "list:100", "attempts": 1 }));
+    let reply = guess(address, &id, 1);
+
+    assert_eq!(reply.status, 200);
+    assert_eq!(reply.body["result"], "less");
+    assert_eq!(reply.body["remaining"], 0);
+    assert_eq!(reply.body["status"], "lost");
+    assert_eq!(reply.body["secret"], 100);
+}
+
+#[test]
+fn the_same_seed_picks_the_same_secret() {
+    let address = server();
+    let first = create(address, json!({ "seed": 7 }));
+    let second = create(address, json!({ "seed": 7 }));
+    assert_ne!(first, second);
+
+    let first = solve(address, &first, 1, 100);
+    let second = solve(address, &second, 1, 100);
+    assert_eq!(first["secret"], second["secret"]);
+    assert_eq!(first["attempt"], second["attempt"]);
+}
+
+#[test]
+fn bad_guesses_are_explained_and_cost_nothing() {
+    let address = server();
+    let id = create(address, json!({}));
+    let path = format!("/games/{id}/guesses");
+
+    for (body, code) in [
+        ("not json", "invalid_json"),
+        (r#"{"guess": "fifty"}"#, "invalid_request"),
+        (r#"{"answer": 50}"#, "invalid_request"),
+        (r#"{"guess": 50, "version": 2}"#, "unsupported_version"),
+        (r#"{"guess": 2.5}"#, "invalid_guess"),
+        (r#"{"guess": 101}"#, "out_of_range"),
+        (r#"{"guess": -1}"#, "out_of_range"),
+    ] {
+        let response = request(address, "POST", &path, body);
+        assert_eq!(response.status, 400, "{body}");
+        assert_eq!(response.body["code"], code, "{body}");
+        assert!(response.body["message"].as_str().is_some_and(|message| !message.is_empty()));
+    }
+
+    let state = request(address, "GET", &format!("/games/{id}"), "");
+    assert_eq!(state.body["attempts"], 0);
+}
+
+#[test]
+fn unknown_pages_and_methods() {
+    let address = server();
+
+    let missing = request(address, "GET", "/games/0123456789abcdef", "");
+    assert_eq!(missing.status, 404);
+    assert_eq!(missing.body["code"], "not_found");
+    assert_eq!(guess(address, "0123456789abcdef", 50).status, 404);
+    assert_eq!(request(address, "GET", "/", "").status, 404);
+
+    let wrong = request(address, "GET", "/games", "");
+    assert_eq!(wrong.status, 405);
+    assert_eq!(wrong.header("allow"), Some("POST"));
+    let id = create(address, json!({}));
+    let wrong = request(address, "DELETE", &format!("/games/{id}"), "");
+    assert_eq!(wrong.status, 405);
+    assert_eq!(wrong.header("allow"), Some("GET"));
+}
+
+#[test]
+fn games_expire_when_left_alone() {
+    let address = start(ApiSettings {
+        session_timeout: Duration::from_millis(300),
+        ..settings()
+    });
+    let idle = create(address, json!({}));
+    let busy = create(address, json!({}));
+
+    // Asking about a game keeps it alive.
+    for _ in 0..5 {
+        thread::sleep(Duration::from_millis(100));
+        assert_eq!(request(address, "GET", &format!("/games/{busy}"), "").status, 200);
+    }
+
+    assert_eq!(request(address, "GET", &format!("/games/{idle}"), "").status, 404);
+    assert_eq!(guess(address, &idle, 50).status, 404);
+}
+
+#[test]
+fn new_games_are_refused_while_the_server_is_full() {
+    let address = start(ApiSettings {
+        session_timeout: Duration::from_millis(300),
+        max_games: 2,
+        ..settings()
+    });
+    create(address, json!({}));
+    create(address, json!({}));
+
+    let refused = request(address, "POST", "/games", "");
+    assert_eq!(refused.status, 503);
+    assert_eq!(refused.body["code"], "busy");
+    assert_eq!(refused.body["message"], "the server already has 2 games; try again once some have expired");
+
+    thread::sleep(Duration::from_millis(400));
+    assert_eq!(request(address, "POST", "/games", "").status, 201);
+}
+
+/// Connect and wait briefly for the server to speak first, which it
+/// only does to turn the connection away.
+fn turned_away(address: SocketAddr) -> Option<String> {
+    let mut stream = TcpStream::connect(address).unwrap();
+    stream.set_read_timeout(Some(Duration::from_millis(200))).unwrap();
+    let mut response = String::new();
+    match stream.read_to_string(&mut response) {
+        Ok(_) => Some(response),
+        Err(err) if matches!(err.kind(), ErrorKind::WouldBlock | ErrorKind::TimedOut) => None,
+        Err(err) => panic!("{err}"),
+    }
+}
+
+#[test]
+fn connections_beyond_the_limit_are_turned_away() {
+    let address = start(ApiSettings {
+        max_connections: 2,
+        ..settings()
+    });
+    // A request on each makes sure the server has taken both.
+    let mut first = TcpStream::connect(address).unwrap();
+    let mut second = TcpStream::connect(address).unwrap();
+    for stream in [&mut first, &mut second] {
+        assert_eq!(exchange(stream, "POST", "/games", "", false).status, 201);
+    }
+
+    let response = turned_away(address).expect("a third connection should be turned away");
+    assert!(response.starts_with("HTTP/1.1 503 Service Unavailable\r\n"), "{response}");
+    assert!(response.contains(r#""code":"busy""#), "{response}");
+
+    // Once a connection closes, there is room for another.
+    drop(first);
+    assert!((0..25).any(|_| turned_away(address).is_none()));
+    assert_eq!(exchange(&mut second, "GET", "/games/none", "", true).status, 404);
+}
+
+#[test]
+fn game_ids_do_not_follow_from_the_seed() {
+    // Two servers with the same seed draw the same secrets, but not the
+    // same ids.
+    let (one, two) = (server(), server());
+    let (first, second) = (create(one, json!({})), create(two, json!({})));
+    assert_ne!(first, second);
+    let secret = |address, id: &str| solve(address, id, 1, 100)["secret"].clone();
+    assert_eq!(secret(one, &first), secret(two, &second));
+}
+
+#[test]
+fn a_connection_can_carry_several_requests() {
+    let address = server();
+    let mut stream = TcpStream::connect(address).unwrap();
+
+    let created = exchange(&mut stream, "POST", "/games", "", false);
+    assert_eq!(created.status, 201);
+    assert_eq!(created.header("connection"), Some("keep-alive"));
+    let id = created.body["id"].as_str().unwrap();
+
+    let reply = exchange(&mut stream, "POST", &format!("/games/{id}/guesses"), r#"{"guess": 50}"#, false);
+    assert_eq!(reply.status, 200);
+    let state = exchange(&mut stream, "GET", &format!("/games/{id}"), "", true);
+    assert_eq!(state.body["attempts"], 1);
+    assert_eq!(state.header("connection"), Some("close"));
+}
+
+#[test]
+fn malformed_requests_get_a_bad_request() {
+    let address = server();
+    let mut stream = TcpStream::connect(address).unwrap();
+    stream.write_all(b"HELLO\r\n\r\n").unwrap();
+
+    let mut response = String::new();
+    stream.read_to_string(&mut response).unwrap();
+    assert!(response.starts_with("HTTP/1.1 400 Bad Request\r\n"), "{response}");
+}
+
+#[test]
+fn many_clients_play_at_once() {
+    let address = server();
+    let players: Vec<_> = (0..16)
+        .map(|_| {
+            thread::spawn(move || {
+                let id = create(address, json!({}));
+                solve(address, &id, 1, 100)
+            })
+        })
+        .collect();
+
+    for player in players {
+        let last = player.join().unwrap();
+        assert_eq!(last["status"], "won");
+        assert!(last["attempt"].as_u64().unwrap() <= 7);
+    }
+}
+
+#[test]
+fn many_clients_can_share_a_game() {
+    let address = server();
+    let id = create(address, json!({ "secret": "list:100", "attempts": "unlimited" }));
+    let players: Vec<_> = (0..8)
+        .map(|player| {
+            let id = id.clone();
+            thread::spawn(move || {
+                (0..10)
+                    .map(|turn| {
+                        let reply = guess(address, &id, player * 10 + turn + 1);
+                        assert_eq!(reply.status, 200);
+                        reply.body["attempt"].as_u64().unwrap()
+                    })
+                    .collect::<Vec<_>>()
+            })
+        })
+        .collect();
+
+    // Every guess got an attempt number of its own.
+    let mut attempts: Vec<u64> = players
+        .into_iter()
+        .flat_map(|player| player.join().unwrap())
+        .collect();
+    attempts.sort_unstable();
+    assert_eq!(attempts, (1..=80).collect::<Vec<_>>());
+
+    let state = request(address, "GET", &format!("/games/{id}"), "");
+    assert_eq!(state.body["attempts"], 80);
+    assert_eq!(state.body["status"], "playing");
+}
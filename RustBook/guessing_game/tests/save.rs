@@ -0,0 +1,59 @@
+// Saving a game and loading it again should give back the same game:
+// the secret, the guesses, the time played and where the random number
+// generator was.
+
+use guessing_game::save::SavedGame;
+use guessing_game::{Config, Game};
+use rand::rngs::StdRng;
+use rand::{RngCore, SeedableRng};
+use std::fs;
+use std::process;
+use std::time::Duration;
+
+fn config(args: &[&str]) -> Config {
+    let args = ["guessing_game"].iter().chain(args).map(|arg| arg.to_string());
+    Config::build(args).unwrap()
+}
+
+#[test]
+fn a_saved_game_loads_as_it_was() {
+    let config = config(&["--min", "1", "--max", "1000", "--attempts", "12"]);
+    let game = Game::with_secret(1, 1000, 613)
+        .with_max_attempts(Some(12))
+        .resume(vec![500, 750, 625], Duration::from_secs(42));
+    let mut rng = StdRng::seed_from_u64(7);
+    // Capturing draws the resumed game's seed from the generator, so a
+    // copy taken first shows what the loaded generator should be.
+    let mut expected_rng = {
+        let mut copy = rng.clone();
+        let mut seed = [0u8; 32];
+        copy.fill_bytes(&mut seed);
+        StdRng::from_seed(seed)
+    };
+
+    let path = std::env::temp_dir().join(format!("guessing_game-save-{}.json", process::id()));
+    SavedGame::capture(&game, &config, &mut rng).save(&path).unwrap();
+    let loaded = SavedGame::load(&path);
+    fs::remove_file(&path).unwrap();
+    let (restored, mut restored_rng) = loaded.unwrap().restore().unwrap();
+
+    assert_eq!(restored.secret(), 613);
+    assert_eq!((restored.low(), restored.high()), (1, 1000));
+    assert_eq!(restored.max_attempts(), Some(12));
+    assert_eq!(restored.history(), &[500, 750, 625]);
+    assert_eq!(restored.attempts(), 3);
+    assert!(restored.elapsed() >= Duration::from_secs(42));
+    assert!(restored.elapsed() < Duration::from_secs(43));
+    for _ in 0..4 {
+        assert_eq!(restored_rng.next_u64(), expected_rng.next_u64());
+    }
+}
+
+#[test]
+fn an_edited_save_is_refused() {
+    let config = config(&[]);
+    let game = Game::with_secret(1, 100, 37).resume(vec![50], Duration::from_secs(3));
+    let mut saved = SavedGame::capture(&game, &config, &mut StdRng::seed_from_u64(1));
+    saved.guesses.push(37);
+    assert!(saved.restore().is_err());
+}
@@ -0,0 +1,195 @@
+// Integration tests for the multiplayer server. Each test starts a
+// server on a free port on localhost and connects clients to it with
+// plain TcpStreams, the same way netcat or `guessing_game connect` would.
+
+use guessing_game::seeded_rng;
+use guessing_game::secret::Distribution;
+use guessing_game::server::{self, RoomSettings};
+use std::io::{BufRead, BufReader, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::thread;
+use std::time::Duration;
+
+const SEED: u64 = 7;
+
+/// Start a server for games between 1 and 100 and return its address.
+fn server() -> SocketAddr {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let address = listener.local_addr().unwrap();
+    let settings = RoomSettings {
+        low: 1,
+        high: 100,
+        max_attempts: Some(7),
+        secret: Distribution::Uniform,
+        seed: Some(SEED),
+    };
+    thread::spawn(move || server::serve(listener, settings));
+    address
+}
+
+/// The secret of the first room opened on a server from `server()`.
+fn first_secret() -> u32 {
+    Distribution::Uniform
+        .source(1, 100)
+        .unwrap()
+        .draw(&mut seeded_rng(Some(SEED)))
+}
+
+struct Client {
+    stream: TcpStream,
+    reader: BufReader<TcpStream>,
+}
+
+impl Client {
+    /// Connect and read the welcome line.
+    fn connect(address: SocketAddr) -> Client {
+        let stream = TcpStream::connect(address).unwrap();
+        // A missing line fails the test rather than hanging it.
+        stream.set_read_timeout(Some(Duration::from_secs(5))).unwrap();
+        let reader = BufReader::new(stream.try_clone().unwrap());
+        let mut client = Client { stream, reader };
+        assert!(client.read().starts_with("Welcome to the guessing game!"));
+        client
+    }
+
+    fn send(&mut self, line: &str) {
+        writeln!(self.stream, "{line}").unwrap();
+    }
+
+    fn read(&mut self) -> String {
+        let mut line = String::new();
+        self.reader.read_line(&mut line).unwrap();
+        line.trim_end().to_string()
+    }
+
+    fn expect(&mut self, expected: &str) {
+        assert_eq!(self.read(), expected);
+    }
+
+    /// Join `room` as `name`, reading the lines that come back.
+    fn join(&mut self, room: &str, name: &str, players: usize) {
+        self.send(&format!("join {room} {name}"));
+        self.expect(&format!("{name} joined {room} ({players} players)."));
+        self.expect("Round 1: guess the number between 1 and 100!");
+    }
+}
+
+/// Two clients in the same room, alice and bob.
+fn two_players(address: SocketAddr) -> (Client, Client) {
+    let mut alice = Client::connect(address);
+    alice.join("den", "alice", 1);
+    let mut bob = Client::connect(address);
+    bob.join("den", "bob", 2);
+    alice.expect("bob joined den (2 players).");
+    (alice, bob)
+}
+
+#[test]
+fn rooms_are_listed() {
+    let address = server();
+    let mut carol = Client::connect(address);
+    carol.send("rooms");
+    carol.expect("There are no rooms yet.");
+
+    let (_alice, _bob) = two_players(address);
+    carol.send("rooms");
+    carol.expect("den: 2 players, round 1");
+}
+
+#[test]
+fn guessing_needs_a_room() {
+    let mut alice = Client::connect(server());
+    alice.send("50");
+    alice.expect("error: join a room first with \"join <room> <name>\"");
+}
+
+#[test]
+fn names_are_unique_in_a_room() {
+    let address = server();
+    let (_alice, _bob) = two_players(address);
+    let mut other = Client::connect(address);
+    other.send("join den alice");
+    other.expect("error: someone in den is already called alice");
+}
+
+#[test]
+fn a_failed_join_stays_in_the_old_room() {
+    let address = server();
+    let (mut alice, _bob) = two_players(address);
+    let mut carol = Client::connect(address);
+    carol.join("hall", "carol", 1);
+    carol.send("join den alice");
+    carol.expect("error: someone in den is already called alice");
+
+    carol.send("rooms");
+    let mut rooms = [carol.read(), carol.read()];
+    rooms.sort();
+    assert_eq!(rooms, ["den: 2 players, round 1", "hall: 1 players, round 1"]);
+
+    // Nobody in den heard about it.
+    alice.send("rooms");
+    assert!(alice.read().ends_with("round 1"));
+}
+
+#[test]
+fn guesses_and_the_winner_are_broadcast() {
+    let (mut alice, mut bob) = two_players(server());
+    let secret = first_secret();
+
+    let (wrong, message) = if secret == 100 { (1, "Too small!") } else { (100, "Too big!") };
+    alice.send(&wrong.to_string());
+    for client in [&mut alice, &mut bob] {
+        client.expect(&format!("alice guessed {wrong}: {message}"));
+    }
+
+    bob.send(&secret.to_string());
+    for client in [&mut alice, &mut bob] {
+        client.expect(&format!("bob guessed {secret}: You win!"));
+        client.expect("bob wins round 1 in 1 guesses!");
+        client.expect("Round 2: guess the number between 1 and 100!");
+    }
+}
+
+#[test]
+fn disconnecting_leaves_the_room() {
+    let address = server();
+    let (mut alice, bob) = two_players(address);
+    drop(bob);
+    alice.expect("bob left den.");
+
+    alice.send("rooms");
+    alice.expect("den: 1 players, round 1");
+}
+
+#[test]
+fn the_round_ends_when_the_last_player_still_guessing_leaves() {
+    let (mut alice, bob) = two_players(server());
+    let secret = first_secret();
+
+    // Alice uses up her 7 guesses without finding the secret.
+    for guess in (1..=100).filter(|&guess| guess != secret).take(7) {
+        alice.send(&guess.to_string());
+        assert!(alice.read().starts_with(&format!("alice guessed {guess}: Too")));
+    }
+    alice.expect("You are out of attempts until the next round.");
+
+    drop(bob);
+    alice.expect("bob left den.");
+    alice.expect(&format!("Everyone is out of attempts. The secret number was {secret}."));
+    alice.expect("Round 2: guess the number between 1 and 100!");
+}
+
+#[test]
+fn a_broken_connection_leaves_the_room() {
+    let address = server();
+    let (mut alice, mut bob) = two_players(address);
+    // A line that isn't UTF-8 fails the read on the server's side.
+    bob.stream.write_all(b"\xff\n").unwrap();
+    alice.expect("bob left den.");
+
+    // The name is free again.
+    let mut again = Client::connect(address);
+    again.send("join den bob");
+    again.expect("bob joined den (2 players).");
+    alice.expect("bob joined den (2 players).");
+}
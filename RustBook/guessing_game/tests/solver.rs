@@ -0,0 +1,34 @@
+// Binary search should never need more guesses than optimal_attempts
+// says, whatever the secret is.
+
+use guessing_game::game::{optimal_attempts, range_size};
+use guessing_game::solver::{auto_play, BinarySearch};
+use guessing_game::Game;
+
+/// Solve the game for `secret` and return the number of guesses.
+fn binary_search(low: u32, high: u32, secret: u32) -> usize {
+    let mut game = Game::with_secret(low, high, secret);
+    let last = auto_play(&mut game, &mut BinarySearch::new(low, high), |_| {});
+    assert!(last.is_some_and(|outcome| outcome.is_win()), "{secret} in {low}..={high} wasn't found");
+    game.attempts()
+}
+
+#[test]
+fn binary_search_finds_every_secret_in_small_ranges() {
+    for (low, high) in [(1, 1), (1, 2), (1, 10), (1, 100), (0, 127), (5, 1000), (1, 1024)] {
+        let bound = optimal_attempts(range_size(low, high));
+        let worst = (low..=high).map(|secret| binary_search(low, high, secret)).max();
+        assert!(worst <= Some(bound), "{low}..={high} took {worst:?} guesses, more than {bound}");
+    }
+}
+
+#[test]
+fn binary_search_stays_within_the_bound_at_the_ends_of_the_insane_range() {
+    let (low, high) = (1, u32::MAX);
+    let bound = optimal_attempts(range_size(low, high));
+    let middle = low / 2 + high / 2;
+    for secret in (low..low + 100).chain(middle - 50..middle + 50).chain(high - 99..=high) {
+        let attempts = binary_search(low, high, secret);
+        assert!(attempts <= bound, "{secret} took {attempts} guesses, more than {bound}");
+    }
+}
@@ -0,0 +1,88 @@
+// The tournament's statistics, and strategy programs that misbehave.
+
+use guessing_game::protocol::Setup;
+use guessing_game::secret::Distribution;
+use guessing_game::tournament::{self, CommandStrategy, Results, Settings, Strategy};
+use std::time::{Duration, Instant};
+
+fn results(attempts: &[usize]) -> Results {
+    Results {
+        name: String::from("test"),
+        attempts: attempts.to_vec(),
+        wins: attempts.len(),
+    }
+}
+
+#[test]
+fn the_median_of_an_odd_number_of_games_is_the_middle_one() {
+    assert_eq!(results(&[9, 1, 5]).median(), 5.0);
+    assert_eq!(results(&[4]).median(), 4.0);
+}
+
+#[test]
+fn the_median_of_an_even_number_of_games_is_between_the_middle_two() {
+    assert_eq!(results(&[7, 1, 4, 2]).median(), 3.0);
+    assert_eq!(results(&[5, 6]).median(), 5.5);
+    assert_eq!(results(&[]).median(), 0.0);
+}
+
+#[test]
+fn the_histogram_has_a_row_for_every_number_of_guesses() {
+    assert_eq!(results(&[3, 5, 3, 6]).histogram(), [(3, 3, 2), (4, 4, 0), (5, 5, 1), (6, 6, 1)]);
+    assert_eq!(results(&[]).histogram(), []);
+}
+
+#[test]
+fn a_wide_histogram_is_bucketed() {
+    // 1 to 45 guesses is 45 different numbers, so 3 to a row.
+    let attempts: Vec<usize> = (1..=45).chain([1, 2, 45]).collect();
+    let rows = results(&attempts).histogram();
+    assert_eq!(rows.len(), 15);
+    assert_eq!(rows[0], (1, 3, 5));
+    assert_eq!(rows[1], (4, 6, 3));
+    assert_eq!(rows[14], (43, 45, 4));
+    assert_eq!(rows.iter().map(|&(_, _, games)| games).sum::<usize>(), attempts.len());
+
+    // A bucket that doesn't divide the spread evenly leaves the last
+    // row short.
+    let rows = results(&[1, 22]).histogram();
+    assert_eq!(rows.len(), 11);
+    assert_eq!(rows[10], (21, 22, 1));
+}
+
+fn play_against(strategy: CommandStrategy) -> Result<Vec<Results>, String> {
+    let settings = Settings {
+        low: 1,
+        high: 100,
+        max_attempts: None,
+        secret: Distribution::Uniform,
+        games: 3,
+        seed: Some(1),
+    };
+    let mut strategies: Vec<Box<dyn Strategy>> = vec![Box::new(strategy)];
+    tournament::run(&mut strategies, &settings)
+}
+
+fn start(command: &str) -> CommandStrategy {
+    let setup = Setup {
+        difficulty: None,
+        distribution: String::from("uniform"),
+    };
+    CommandStrategy::start(command, setup).unwrap()
+}
+
+#[test]
+fn a_program_that_never_answers_runs_out_of_time() {
+    let started = Instant::now();
+    let err = play_against(start("sleep 30").with_time_limit(Duration::from_millis(200))).unwrap_err();
+    assert!(err.contains("took more than 0.2 seconds"), "{err}");
+    assert!(started.elapsed() < Duration::from_secs(10));
+}
+
+#[test]
+fn a_program_that_never_guesses_is_stopped() {
+    let started = Instant::now();
+    let err = play_against(start("yes")).unwrap_err();
+    assert!(err.contains("lines that weren't guesses"), "{err}");
+    assert!(started.elapsed() < Duration::from_secs(10));
+}